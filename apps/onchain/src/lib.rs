@@ -1,8 +1,8 @@
 #![no_std]
 #![allow(unexpected_cfgs)]
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, symbol_short, token, Address, Env, Symbol,
-    Vec,
+    contract, contractclient, contracterror, contractimpl, contracttype, symbol_short, token,
+    xdr::ToXdr, Address, Bytes, BytesN, Env, IntoVal, Map, String, Symbol, Val, Vec,
 };
 
 impl VaultixEscrow {
@@ -15,13 +15,11 @@ impl VaultixEscrow {
 
         let hash_bytes = soroban_sdk::BytesN::<32>::from_array(&env, &new_wasm_hash);
 
-        // Emit ContractUpgraded event
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "ContractUpgraded"),
-            ),
-            hash_bytes.clone(),
+        publish_event(
+            &env,
+            ContractUpgradedEvent {
+                wasm_hash: hash_bytes.clone(),
+            },
         );
 
         env.deployer().update_current_contract_wasm(hash_bytes);
@@ -35,6 +33,9 @@ pub enum MilestoneStatus {
     Pending,
     Released,
     Disputed,
+    Submitted, // Delivered by the recipient, awaiting the depositor's review
+    Refunded,  // Funds returned to the depositor
+    Cancelled, // Escrow cancelled or settled before the milestone was paid
 }
 
 #[contracttype]
@@ -43,6 +44,138 @@ pub struct Milestone {
     pub amount: i128,
     pub status: MilestoneStatus,
     pub description: Symbol,
+    pub due_date: u64,      // Expected delivery time; 0 when not set
+    pub review_period: u64, // Seconds the depositor has to act on a delivery; 0 disables auto-release
+    pub submitted_at: u64,  // When the recipient last submitted the milestone; 0 until then
+    pub kind: MilestoneKind,
+    pub claimed: i128, // Amount already paid out of this milestone
+    pub condition: ReleaseCondition,
+    pub payees: Vec<Payee>, // Split of this milestone's payout; empty pays the escrow recipient
+    pub approval: ApprovalRule,
+    pub metadata: MilestoneMetadata,
+}
+
+/// Human-readable description of a deliverable. `description` stays as a
+/// short label; the full spec lives off-chain and is pinned by `spec_hash`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MilestoneMetadata {
+    pub title: String,
+    pub spec_hash: BytesN<32>, // SHA-256 of the spec document
+    pub uri: String,           // Where the spec can be fetched; empty when not published
+    pub criteria: Vec<u32>,    // Acceptance criteria ids from the spec
+}
+
+impl MilestoneMetadata {
+    /// Metadata for milestones that only carry a `description`.
+    pub fn empty(env: &Env) -> Self {
+        MilestoneMetadata {
+            title: String::from_str(env, ""),
+            spec_hash: BytesN::from_array(env, &[0; 32]),
+            uri: String::from_str(env, ""),
+            criteria: Vec::new(env),
+        }
+    }
+}
+
+/// Who must sign off before a milestone is released.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ApprovalRule {
+    /// The depositor alone
+    Depositor,
+    /// Either the depositor or the named approver, e.g. an escrow agent
+    DepositorOrApprover(Address),
+    /// Both the depositor and the named approver, e.g. an inspector
+    DepositorAndApprover(Address),
+}
+
+/// Share of a milestone paid to one address, in basis points of the payout.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Payee {
+    pub address: Address,
+    pub bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReleaseCondition {
+    /// Released by the depositor only
+    None,
+    /// Also releasable by anyone once the given verifier contract confirms delivery
+    Verifier(Address),
+    /// Releasable by anyone revealing a preimage whose SHA-256 matches the hash,
+    /// until the timelock; afterwards refundable to the depositor
+    HashLock(BytesN<32>, u64),
+}
+
+/// Interface an external verifier contract must implement for
+/// `ReleaseCondition::Verifier` milestones.
+#[contractclient(name = "VerifierClient")]
+pub trait MilestoneVerifier {
+    fn verify(env: Env, escrow_id: u64, milestone_index: u32) -> bool;
+}
+
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MilestoneKind {
+    /// Paid in full when released
+    Fixed,
+    /// Accrues linearly from start to end timestamp, claimable by the recipient
+    Vesting(u64, u64),
+    /// Claimable in full by the recipient from the given timestamp
+    Scheduled(u64),
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct PaymentSchedule {
+    pub period_amount: i128,
+    pub periods: u32,
+    pub start: u64,
+    pub interval: u64, // Seconds between payments, e.g. 604_800 for weekly
+    pub cliff: u64,    // Seconds after start before the first payment unlocks; 0 for none
+    pub notice: u64,   // Seconds of notice the depositor must give to cancel future periods
+}
+
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Party {
+    Depositor,
+    Recipient,
+}
+
+/// M-of-N signers acting for one side of an escrow. Once set, the party
+/// address alone can no longer take any `PartyAction`: releasing,
+/// confirming, rejecting, cancelling, refunding, disputing, settling,
+/// appealing or assigning go through `approve_action` until `threshold`
+/// signers agree. The party address itself still funds the escrow, submits
+/// and claims milestones, completes a fully paid escrow, sets assignment
+/// consent before funding and edits metadata, none of which can move funds
+/// away from its side.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct SignerSet {
+    pub signers: Vec<Address>,
+    pub threshold: u32,
+}
+
+#[contracttype]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum PartyAction {
+    ReleaseMilestone(u32),
+    ConfirmDelivery(u32),
+    CancelEscrow,
+    RaiseDispute,
+    ProposeSettlement(i128),
+    AcceptSettlement(i128),
+    WithdrawDispute,
+    AssignRecipient(Address),
+    RejectMilestone(u32, Symbol),
+    CancelSubscription,
+    RefundExpired,
+    AppealRuling,
 }
 
 #[contracttype]
@@ -54,7 +187,10 @@ pub enum EscrowStatus {
     Cancelled, // Escrow cancelled, funds refunded
     Disputed,
     Resolved,
-    Expired, // Escrow expired and refunded to depositor
+    Expired,       // Escrow expired and refunded to depositor
+    RulingPending, // Dispute ruled, funds held until the appeal window lapses
+    Appealed,      // Ruling appealed, awaiting the secondary arbiter
+    Archived,      // Record removed; only the receipt remains
 }
 
 #[contracttype]
@@ -63,8 +199,11 @@ pub enum Resolution {
     None,
     Depositor,
     Recipient,
+    Settled,
 }
 
+/// Pause switch written under `state` by contract versions before the pause flags.
+/// Only read, so a deployment paused before an upgrade stays paused.
 #[contracttype]
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum ContractState {
@@ -80,822 +219,4796 @@ pub struct Escrow {
     pub token_address: Address,
     pub total_amount: i128,
     pub total_released: i128,
-    pub milestones: Vec<Milestone>,
+    pub total_refunded: i128, // Returned to the depositor, fees included
+    pub milestone_count: u32, // Milestones are stored individually; see `get_milestones`
+    pub open_milestones: u32, // Milestones not yet released, refunded or cancelled
     pub status: EscrowStatus,
     pub deadline: u64,
     pub resolution: Resolution,
+    pub assignment_consent: bool, // Whether the depositor must co-sign `assign_recipient`
+    pub template_id: u64,         // Template the escrow was created from; 0 for none
+    pub hashlock_until: u64,      // Latest hash-lock timelock of any milestone; 0 for none
 }
 
-#[contracterror]
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum Error {
-    EscrowNotFound = 1,
-    EscrowAlreadyExists = 2,
-    MilestoneNotFound = 3,
-    MilestoneAlreadyReleased = 4,
-    UnauthorizedAccess = 5,
-    InvalidMilestoneAmount = 6,
-    TotalAmountMismatch = 7,
-    InsufficientBalance = 8,
-    EscrowNotActive = 9,
-    VectorTooLarge = 10,
-    ZeroAmount = 11,
-    InvalidDeadline = 12,
-    SelfDealing = 13,
-    EscrowAlreadyFunded = 14,
-    TokenTransferFailed = 15,
-    TreasuryNotInitialized = 16,
-    InvalidFeeConfiguration = 17,
-    AdminNotInitialized = 18,
-    AlreadyInitialized = 19,
-    InvalidEscrowStatus = 20,
-    AlreadyInDispute = 21,
-    InvalidWinner = 22,
-    ContractPaused = 23,
-    DeadlineNotReached = 24,
-    InvalidStatusForRefund = 25,
-    NoFundsToRefund = 26,
-    Unauthorized = 27,
+/// What is kept of a closed escrow after `archive_escrow` removes its record.
+/// `record_hash` is the SHA-256 of the XDR-encoded header and event checkpoint;
+/// the checkpoint's hash chain covers every change made to the milestones.
+/// For an escrow that was funded, `total_released + total_refunded` equals
+/// `total_amount` whichever way it closed; both include the fees taken out.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowReceipt {
+    pub depositor: Address,
+    pub recipient: Address,
+    pub token_address: Address,
+    pub total_amount: i128,
+    pub total_released: i128,
+    pub total_refunded: i128,
+    pub final_status: EscrowStatus,
+    pub resolution: Resolution,
+    pub record_hash: BytesN<32>,
+    pub archived_at: u64,
 }
 
-const DEFAULT_FEE_BPS: i128 = 50;
-const BPS_DENOMINATOR: i128 = 10000;
-
-#[contract]
-pub struct VaultixEscrow;
+/// Head of an escrow's event hash chain. Each escrow event advances it to
+/// `sha256(hash || xdr((topics, data)))`, starting from 32 zero bytes.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowCheckpoint {
+    pub sequence: u64, // Number of events folded into `hash`
+    pub hash: BytesN<32>,
+}
 
-#[contractimpl]
-impl VaultixEscrow {
-    pub fn initialize(env: Env, treasury: Address, fee_bps: Option<i128>) -> Result<(), Error> {
-        treasury.require_auth();
+/// Milestone work left over by an operation that touches every milestone.
+/// Each call handles at most `SWEEP_PAGE` milestones; `sweep_milestones`
+/// carries on from `next`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MilestoneSweep {
+    pub action: SweepAction,
+    pub next: u32,      // Index of the next milestone to visit
+    pub end: u32,       // Milestone count when the work started
+    pub refunded: i128, // Returned to the depositor by `Refund` pages so far, after fees
+}
 
-        let fee = fee_bps.unwrap_or(DEFAULT_FEE_BPS);
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SweepAction {
+    /// A dispute raised at the first timestamp was withdrawn at the second:
+    /// deliveries are reset and vesting shifted, then the escrow returns to
+    /// the given status
+    Resume(u64, u64, EscrowStatus),
+    /// Open milestones close with the status, sharing the first amount pro
+    /// rata to what each still owed out of the second; zero just closes them
+    Payout(MilestoneStatus, i128, i128),
+    /// Open milestones close with the status at the timestamp: what has
+    /// vested or unlocked by then is paid to the recipient, and the rest goes
+    /// back to the depositor, less the platform fee
+    Refund(MilestoneStatus, u64),
+    /// Milestone entries of an archived escrow are removed
+    Purge,
+}
 
-        if !(0..=BPS_DENOMINATOR).contains(&fee) {
-            return Err(Error::InvalidFeeConfiguration);
-        }
+/// Integrator-supplied labels for an escrow, stored beside it. `external_ref`
+/// is indexed per depositor for `find_by_external_ref`; empty when not set.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowMetadata {
+    pub external_ref: String,
+    pub tags: Vec<Symbol>,
+    pub entries: Map<Symbol, String>,
+}
 
-        env.storage()
-            .instance()
-            .set(&symbol_short!("treasury"), &treasury);
-        env.storage()
-            .instance()
-            .set(&symbol_short!("fee_bps"), &fee);
+/// One step of a template, sized as a share of the escrow total.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct TemplateMilestone {
+    pub description: Symbol,
+    pub bps: u32,
+}
 
-        let vaultix_topic = Symbol::new(&env, "Vaultix");
+/// Reusable milestone layout. The optional default arbiter is stored
+/// alongside it rather than in the struct.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct EscrowTemplate {
+    pub owner: Address,
+    pub milestones: Vec<TemplateMilestone>,
+    pub default_duration: u64, // Seconds from creation to the escrow deadline
+}
 
-        // Emit RoleUpdated(role, old_addr, new_addr) - using Option for old_addr
-        env.events().publish(
-            (
-                vaultix_topic.clone(),
-                Symbol::new(&env, "RoleUpdated"),
-                Symbol::new(&env, "Treasury"),
-            ),
-            (Option::<Address>::None, treasury.clone()),
-        );
+/// An escrow proposed by the recipient, waiting for the depositor to accept it.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct EscrowRequest {
+    pub depositor: Address,
+    pub recipient: Address,
+    pub token_address: Address,
+    pub milestones: Vec<Milestone>,
+    pub deadline: u64,
+    pub expires_at: u64,
+}
 
-        // Emit FeeUpdated(scope, key, old_fee, new_fee)
-        env.events().publish(
-            (vaultix_topic, Symbol::new(&env, "FeeUpdated")),
-            (
-                Symbol::new(&env, "Global"),
-                Symbol::new(&env, "PlatformFee"),
-                0i128,
-                fee,
-            ),
-        );
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct SettlementProposal {
+    pub proposer: Address,
+    pub recipient_amount: i128,
+}
 
-        Ok(())
-    }
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct Dispute {
+    pub raised_by: Address,
+    pub raised_at: u64,
+    pub previous_status: EscrowStatus, // Status restored if the dispute is withdrawn
+    pub bond: i128,                    // Bond posted by the raiser, in the escrow token
+    pub panel: Vec<Address>, // Arbiters ruling on this dispute; empty for admin resolution
+    pub quorum: u32,
+    pub votes: Vec<ArbiterVote>,
+    pub ruling: Resolution, // Ruling held for appeal; None until one is issued
+    pub ruling_arbiters: Vec<Address>,
+    pub ruled_at: u64,
+    pub appeal_deadline: u64, // Fixed when the ruling is recorded; later config changes do not move it
+    pub appealed: bool,       // Set once the losing party escalates the ruling
+    pub appeal_bond: i128,
+}
 
-    pub fn update_fee(env: Env, new_fee_bps: i128) -> Result<(), Error> {
-        let treasury: Address = env
-            .storage()
-            .instance()
-            .get(&symbol_short!("treasury"))
-            .ok_or(Error::TreasuryNotInitialized)?;
-        treasury.require_auth();
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Ruling {
+    Depositor,
+    Recipient,
+    Abstain,
+}
 
-        if !(0..=BPS_DENOMINATOR).contains(&new_fee_bps) {
-            return Err(Error::InvalidFeeConfiguration);
-        }
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ArbiterVote {
+    pub arbiter: Address,
+    pub ruling: Ruling,
+}
 
-        let old_fee: i128 = env
-            .storage()
-            .instance()
-            .get(&symbol_short!("fee_bps"))
-            .unwrap_or(DEFAULT_FEE_BPS);
-        env.storage()
-            .instance()
-            .set(&symbol_short!("fee_bps"), &new_fee_bps);
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ArbiterPanel {
+    pub arbiters: Vec<Address>,
+    pub quorum: u32,     // Matching votes (M of N) needed to execute a ruling
+    pub threshold: i128, // Minimum escrow total that is routed to the panel
+}
 
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "FeeUpdated"),
-            ),
-            (
-                Symbol::new(&env, "Global"),
-                Symbol::new(&env, "PlatformFee"),
-                old_fee,
-                new_fee_bps,
-            ),
-        );
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct AppealConfig {
+    pub window: u64,      // Seconds a ruling can be appealed before it is executed
+    pub bond: i128,       // Bond posted by the appellant, in the escrow token
+    pub arbiter: Address, // Secondary arbiter hearing appeals
+}
 
-        Ok(())
-    }
+// Escrow events. Topics are `("Vaultix", <name>, EVENT_VERSION, escrow_id)` and
+// the data is `(sequence, <name>Event)`, where `sequence` counts the escrow's
+// events from 1 and matches `get_escrow_checkpoint`. Any change to these
+// structs must come with a bump of `EVENT_VERSION`.
 
-    pub fn get_config(env: Env) -> Result<(Address, i128), Error> {
-        let treasury: Address = env
-            .storage()
-            .instance()
-            .get(&symbol_short!("treasury"))
-            .ok_or(Error::TreasuryNotInitialized)?;
-        let fee_bps: i128 = env
-            .storage()
-            .instance()
-            .get(&symbol_short!("fee_bps"))
-            .unwrap_or(DEFAULT_FEE_BPS);
-        Ok((treasury, fee_bps))
-    }
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowCreatedEvent {
+    pub depositor: Address,
+    pub recipient: Address,
+    pub token_address: Address,
+    pub total_amount: i128,
+    pub deadline: u64,
+    pub milestones: Vec<MilestoneMetadata>,
+}
 
-    pub fn set_paused(env: Env, paused: bool) -> Result<(), Error> {
-        let treasury: Address = env
-            .storage()
-            .instance()
-            .get(&symbol_short!("treasury"))
-            .ok_or(Error::TreasuryNotInitialized)?;
-        treasury.require_auth();
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowFundedEvent {
+    pub amount: i128,
+}
 
-        let state = if paused {
-            ContractState::Paused
-        } else {
-            ContractState::Active
-        };
-        env.storage()
-            .instance()
-            .set(&symbol_short!("state"), &state);
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubscriptionCreatedEvent {
+    pub period_amount: i128,
+    pub periods: u32,
+    pub interval: u64,
+    pub cliff: u64,
+}
 
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "PausedStateChanged"),
-            ),
-            (paused, treasury),
-        );
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubscriptionCancelledEvent {
+    pub cancelled_periods: u32,
+    pub refund_amount: i128,
+    pub fee: i128,
+}
 
-        Ok(())
-    }
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MetadataUpdatedEvent {
+    pub caller: Address,
+    pub external_ref: String,
+}
 
-    pub fn init(env: Env, admin: Address) -> Result<(), Error> {
-        if env.storage().persistent().has(&admin_storage_key()) {
-            return Err(Error::AlreadyInitialized);
-        }
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TemplateAppliedEvent {
+    pub template_id: u64,
+}
 
-        admin.require_auth();
-        env.storage().persistent().set(&admin_storage_key(), &admin);
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArbiterAssignedEvent {
+    pub arbiter: Address,
+}
 
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "RoleUpdated"),
-                Symbol::new(&env, "Admin"),
-            ),
-            (Option::<Address>::None, admin),
-        );
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssignmentConsentSetEvent {
+    pub required: bool,
+}
 
-        Ok(())
-    }
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowAssignedEvent {
+    pub previous: Address,
+    pub new_recipient: Address,
+}
 
-    pub fn create_escrow(
-        env: Env,
-        escrow_id: u64,
-        depositor: Address,
-        recipient: Address,
-        token_address: Address,
-        milestones: Vec<Milestone>,
-        deadline: u64,
-    ) -> Result<(), Error> {
-        depositor.require_auth();
-        ensure_not_paused(&env)?;
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignersConfiguredEvent {
+    pub party: Party,
+    pub threshold: u32,
+}
 
-        if depositor == recipient {
-            return Err(Error::SelfDealing);
-        }
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApprovalRecordedEvent {
+    pub signer: Address,
+    pub approvals: u32,
+    pub threshold: u32,
+}
 
-        let storage_key = get_storage_key(escrow_id);
-        if env.storage().persistent().has(&storage_key) {
-            return Err(Error::EscrowAlreadyExists);
-        }
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MilestoneSubmittedEvent {
+    pub milestone_index: u32,
+    pub proof_hash: BytesN<32>,
+    pub submitted_at: u64,
+    pub due_date: u64,
+}
 
-        let total_amount = validate_milestones(&milestones)?;
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MilestoneRejectedEvent {
+    pub milestone_index: u32,
+    pub reason: Symbol,
+}
 
-        let mut initialized_milestones = Vec::new(&env);
-        for milestone in milestones.iter() {
-            let mut m = milestone.clone();
-            m.status = MilestoneStatus::Pending;
-            initialized_milestones.push_back(m);
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MilestoneReleasedEvent {
+    pub milestone_index: u32,
+    pub payout: i128,
+    pub fee: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VestedClaimedEvent {
+    pub milestone_index: u32,
+    pub payout: i128,
+    pub fee: i128,
+    pub claimed: i128, // Total claimed from the milestone so far
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreimageRevealedEvent {
+    pub milestone_index: u32,
+    pub preimage: Bytes,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HashLockRefundedEvent {
+    pub milestone_index: u32,
+    pub refund_amount: i128,
+    pub fee: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisputeRaisedEvent {
+    pub raised_by: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArbiterVoteCastEvent {
+    pub arbiter: Address,
+    pub ruling: Ruling,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArbiterReplacedEvent {
+    pub old_arbiter: Address,
+    pub new_arbiter: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RulingRecordedEvent {
+    pub winner: Address,
+    pub appeal_deadline: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RulingAppealedEvent {
+    pub appellant: Address,
+    pub bond: i128,
+    pub arbiter: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisputeResolvedEvent {
+    pub winner: Address,
+    pub payout: i128,
+    pub arbiter_fee: i128,
+    pub bond: i128,
+    pub bond_recipient: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SettlementProposedEvent {
+    pub proposer: Address,
+    pub recipient_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisputeSettledEvent {
+    pub recipient_amount: i128,
+    pub depositor_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisputeWithdrawnEvent {
+    pub withdrawn_by: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowCompletedEvent {
+    pub total_released: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowCancelledEvent {
+    pub depositor: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RefundExpiredEvent {
+    pub depositor: Address,
+    pub refund_amount: i128,
+    pub refunded_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowArchivedEvent {
+    pub final_status: EscrowStatus,
+    pub record_hash: BytesN<32>,
+}
+
+// Contract events: configuration, templates and escrow requests. Topics are
+// `("Vaultix", <name>, EVENT_VERSION)` and the data is the `<name>Event`
+// struct. They share `EVENT_VERSION` with the escrow events.
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContractUpgradedEvent {
+    pub wasm_hash: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoleUpdatedEvent {
+    pub role: Symbol, // `Treasury` or `Admin`
+    pub address: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeeUpdatedEvent {
+    pub fee: Symbol, // `PlatformFee` or `ArbiterFee`
+    pub old_bps: i128,
+    pub new_bps: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PausedStateChangedEvent {
+    pub paused: bool,
+    pub treasury: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PauseFlagsUpdatedEvent {
+    pub flags: u32,
+    pub treasury: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenPauseUpdatedEvent {
+    pub token: Address,
+    pub flags: u32,
+    pub treasury: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmergencyModeChangedEvent {
+    pub enabled: bool,
+    pub treasury: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PanelConfiguredEvent {
+    pub arbiters: Vec<Address>,
+    pub quorum: u32,
+    pub threshold: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppealConfigUpdatedEvent {
+    pub window: u64,
+    pub bond: i128,
+    pub arbiter: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisputeBondUpdatedEvent {
+    pub token_address: Address,
+    pub old_amount: i128,
+    pub new_amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MilestoneLimitUpdatedEvent {
+    pub old_limit: u32,
+    pub new_limit: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TemplateRegisteredEvent {
+    pub template_id: u64,
+    pub owner: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowRequestedEvent {
+    pub request_id: u64,
+    pub depositor: Address,
+    pub recipient: Address,
+    pub expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowRequestAcceptedEvent {
+    pub request_id: u64,
+    pub escrow_id: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowRequestRejectedEvent {
+    pub request_id: u64,
+    pub depositor: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowRequestCancelledEvent {
+    pub request_id: u64,
+    pub recipient: Address,
+}
+
+/// An event about a single escrow, published with `publish_escrow_event`.
+trait EscrowEvent: IntoVal<Env, Val> {
+    const NAME: &'static str;
+}
+
+/// An event about the contract as a whole, published with `publish_event`.
+trait ContractEvent: IntoVal<Env, Val> {
+    const NAME: &'static str;
+}
+
+macro_rules! events {
+    ($kind:ident { $($event:ident => $name:literal),* $(,)? }) => {
+        $(impl $kind for $event {
+            const NAME: &'static str = $name;
+        })*
+    };
+}
+
+events! { ContractEvent {
+    ContractUpgradedEvent => "ContractUpgraded",
+    RoleUpdatedEvent => "RoleUpdated",
+    FeeUpdatedEvent => "FeeUpdated",
+    PausedStateChangedEvent => "PausedStateChanged",
+    PauseFlagsUpdatedEvent => "PauseFlagsUpdated",
+    TokenPauseUpdatedEvent => "TokenPauseUpdated",
+    EmergencyModeChangedEvent => "EmergencyModeChanged",
+    PanelConfiguredEvent => "PanelConfigured",
+    AppealConfigUpdatedEvent => "AppealConfigUpdated",
+    DisputeBondUpdatedEvent => "DisputeBondUpdated",
+    MilestoneLimitUpdatedEvent => "MilestoneLimitUpdated",
+    TemplateRegisteredEvent => "TemplateRegistered",
+    EscrowRequestedEvent => "EscrowRequested",
+    EscrowRequestAcceptedEvent => "EscrowRequestAccepted",
+    EscrowRequestRejectedEvent => "EscrowRequestRejected",
+    EscrowRequestCancelledEvent => "EscrowRequestCancelled",
+}}
+
+events! { EscrowEvent {
+    EscrowCreatedEvent => "EscrowCreated",
+    EscrowFundedEvent => "EscrowFunded",
+    SubscriptionCreatedEvent => "SubscriptionCreated",
+    SubscriptionCancelledEvent => "SubscriptionCancelled",
+    MetadataUpdatedEvent => "MetadataUpdated",
+    TemplateAppliedEvent => "TemplateApplied",
+    ArbiterAssignedEvent => "ArbiterAssigned",
+    AssignmentConsentSetEvent => "AssignmentConsentSet",
+    EscrowAssignedEvent => "EscrowAssigned",
+    SignersConfiguredEvent => "SignersConfigured",
+    ApprovalRecordedEvent => "ApprovalRecorded",
+    MilestoneSubmittedEvent => "MilestoneSubmitted",
+    MilestoneRejectedEvent => "MilestoneRejected",
+    MilestoneReleasedEvent => "MilestoneReleased",
+    VestedClaimedEvent => "VestedClaimed",
+    PreimageRevealedEvent => "PreimageRevealed",
+    HashLockRefundedEvent => "HashLockRefunded",
+    DisputeRaisedEvent => "DisputeRaised",
+    ArbiterVoteCastEvent => "ArbiterVoteCast",
+    ArbiterReplacedEvent => "ArbiterReplaced",
+    RulingRecordedEvent => "RulingRecorded",
+    RulingAppealedEvent => "RulingAppealed",
+    DisputeResolvedEvent => "DisputeResolved",
+    SettlementProposedEvent => "SettlementProposed",
+    DisputeSettledEvent => "DisputeSettled",
+    DisputeWithdrawnEvent => "DisputeWithdrawn",
+    EscrowCompletedEvent => "EscrowCompleted",
+    EscrowCancelledEvent => "EscrowCancelled",
+    RefundExpiredEvent => "RefundExpired",
+    EscrowArchivedEvent => "EscrowArchived",
+}}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    EscrowNotFound = 1,
+    EscrowAlreadyExists = 2,
+    MilestoneNotFound = 3,
+    MilestoneAlreadyReleased = 4,
+    UnauthorizedAccess = 5,
+    InvalidMilestoneAmount = 6,
+    TotalAmountMismatch = 7,
+    InsufficientBalance = 8,
+    EscrowNotActive = 9,
+    VectorTooLarge = 10,
+    ZeroAmount = 11,
+    InvalidDeadline = 12,
+    SelfDealing = 13,
+    EscrowAlreadyFunded = 14,
+    TokenTransferFailed = 15,
+    TreasuryNotInitialized = 16,
+    /// Also returned for pause flags outside `PAUSE_ALL`
+    InvalidFeeConfiguration = 17,
+    AdminNotInitialized = 18,
+    AlreadyInitialized = 19,
+    InvalidEscrowStatus = 20,
+    AlreadyInDispute = 21,
+    InvalidWinner = 22,
+    ContractPaused = 23,
+    DeadlineNotReached = 24,
+    InvalidStatusForRefund = 25,
+    NoFundsToRefund = 26,
+    Unauthorized = 27,
+    DisputeNotFound = 28,
+    NoSettlementProposal = 29,
+    InvalidSettlementAmount = 30,
+    InvalidBondAmount = 31,
+    InvalidPanelConfiguration = 32,
+    PanelRequired = 33,
+    NotPanelMember = 34,
+    AppealsDisabled = 35,
+    AppealWindowClosed = 36,
+    AppealWindowOpen = 37,
+    MilestoneStateInconsistent = 38,
+    MilestoneNotDelivered = 39,
+    ReviewPeriodActive = 40,
+    InvalidMilestoneStatus = 41,
+    NothingToClaim = 42,
+    NotASubscription = 43,
+    InvalidSchedule = 44,
+    ConditionNotMet = 45,
+    InvalidPreimage = 46,
+    HashLockExpired = 47,
+    HashLockActive = 48,
+    InvalidSignerSet = 49,
+    MultisigRequired = 50,
+    // The contract spec caps error enums at 50 cases; new failures must reuse
+    // an existing code.
+}
+
+const DEFAULT_FEE_BPS: i128 = 50;
+// Operations that can be paused, as bits of the global and per-token flags
+pub const PAUSE_NEW_ESCROWS: u32 = 1 << 0; // Creating escrows, subscriptions and requests
+pub const PAUSE_DEPOSITS: u32 = 1 << 1;
+pub const PAUSE_RELEASES: u32 = 1 << 2; // Submissions, releases, claims and completion
+pub const PAUSE_DISPUTES: u32 = 1 << 3; // Raising, settling and ruling on disputes
+pub const PAUSE_REFUNDS: u32 = 1 << 4; // Exits back to the depositor
+pub const PAUSE_ALL: u32 = (1 << 5) - 1;
+pub const EVENT_VERSION: u32 = 1; // Schema version of all events; see `EscrowEvent` and `ContractEvent`
+
+// TTL policy, in ledgers (~5s each). Every write extends the entry, and the
+// contract instance, back to the full period once a day's worth has elapsed.
+const LEDGERS_PER_DAY: u32 = 17_280;
+const ENTRY_TTL_EXTEND_TO: u32 = 2_000_000; // ~115 days
+const ENTRY_TTL_THRESHOLD: u32 = ENTRY_TTL_EXTEND_TO - LEDGERS_PER_DAY;
+const INSTANCE_TTL_EXTEND_TO: u32 = 2_000_000;
+const INSTANCE_TTL_THRESHOLD: u32 = INSTANCE_TTL_EXTEND_TO - LEDGERS_PER_DAY;
+const BPS_DENOMINATOR: i128 = 10000;
+const MAX_PANEL_SIZE: u32 = 15;
+const DEFAULT_MILESTONE_LIMIT: u32 = 20;
+// Escrows are still created in a single transaction, which bounds the limit;
+// everything that later touches every milestone runs in pages of `SWEEP_PAGE`.
+const MAX_MILESTONE_LIMIT: u32 = 250;
+const SWEEP_PAGE: u32 = DEFAULT_MILESTONE_LIMIT; // So escrows of the default size close in one call
+
+// Per-address indexes are split into pages so no entry grows with the number
+// of escrows or requests that name an address.
+const INDEX_PAGE: u32 = 50;
+const MAX_SIGNERS: u32 = 10;
+const MAX_PAYEES: u32 = 10;
+const MAX_TITLE_LEN: u32 = 128;
+const MAX_URI_LEN: u32 = 256;
+const MAX_CRITERIA: u32 = 16;
+const MAX_EXTERNAL_REF_LEN: u32 = 64;
+const MAX_TAGS: u32 = 10;
+const MAX_METADATA_ENTRIES: u32 = 10;
+const MAX_METADATA_VALUE_LEN: u32 = 256;
+const REQUEST_VALIDITY: u64 = 604_800; // Seconds an escrow request stays open (7 days)
+const MAX_OPEN_REQUESTS: u32 = 20; // Open requests any one recipient can have sent
+
+#[contract]
+pub struct VaultixEscrow;
+
+#[contractimpl]
+impl VaultixEscrow {
+    pub fn initialize(env: Env, treasury: Address, fee_bps: Option<i128>) -> Result<(), Error> {
+        treasury.require_auth();
+
+        let fee = fee_bps.unwrap_or(DEFAULT_FEE_BPS);
+
+        if !(0..=BPS_DENOMINATOR).contains(&fee) {
+            return Err(Error::InvalidFeeConfiguration);
+        }
+
+        env.storage()
+            .instance()
+            .set(&symbol_short!("treasury"), &treasury);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("fee_bps"), &fee);
+        bump_instance_ttl(&env);
+
+        publish_event(
+            &env,
+            RoleUpdatedEvent {
+                role: Symbol::new(&env, "Treasury"),
+                address: treasury,
+            },
+        );
+        publish_event(
+            &env,
+            FeeUpdatedEvent {
+                fee: Symbol::new(&env, "PlatformFee"),
+                old_bps: 0,
+                new_bps: fee,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn update_fee(env: Env, new_fee_bps: i128) -> Result<(), Error> {
+        let treasury: Address = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("treasury"))
+            .ok_or(Error::TreasuryNotInitialized)?;
+        treasury.require_auth();
+
+        if !(0..=BPS_DENOMINATOR).contains(&new_fee_bps) {
+            return Err(Error::InvalidFeeConfiguration);
+        }
+
+        let old_fee: i128 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("fee_bps"))
+            .unwrap_or(DEFAULT_FEE_BPS);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("fee_bps"), &new_fee_bps);
+        bump_instance_ttl(&env);
+
+        publish_event(
+            &env,
+            FeeUpdatedEvent {
+                fee: Symbol::new(&env, "PlatformFee"),
+                old_bps: old_fee,
+                new_bps: new_fee_bps,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_config(env: Env) -> Result<(Address, i128), Error> {
+        let treasury: Address = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("treasury"))
+            .ok_or(Error::TreasuryNotInitialized)?;
+        let fee_bps: i128 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("fee_bps"))
+            .unwrap_or(DEFAULT_FEE_BPS);
+        Ok((treasury, fee_bps))
+    }
+
+    /// Pauses or resumes every operation at once; see `set_pause_flags`.
+    pub fn set_paused(env: Env, paused: bool) -> Result<(), Error> {
+        let treasury = require_treasury(&env)?;
+
+        let flags = if paused { PAUSE_ALL } else { 0 };
+        env.storage()
+            .instance()
+            .set(&symbol_short!("pause"), &flags);
+        env.storage().instance().remove(&symbol_short!("state"));
+        bump_instance_ttl(&env);
+
+        publish_event(&env, PausedStateChangedEvent { paused, treasury });
+
+        Ok(())
+    }
+
+    /// Replaces the global pause flags, a bitmap of `PAUSE_*` operations.
+    /// Bits outside `PAUSE_ALL` are rejected as `InvalidFeeConfiguration`,
+    /// the closest treasury-settings error; there is no pause-specific code.
+    pub fn set_pause_flags(env: Env, flags: u32) -> Result<(), Error> {
+        let treasury = require_treasury(&env)?;
+        if flags & !PAUSE_ALL != 0 {
+            return Err(Error::InvalidFeeConfiguration);
+        }
+
+        env.storage()
+            .instance()
+            .set(&symbol_short!("pause"), &flags);
+        env.storage().instance().remove(&symbol_short!("state"));
+        bump_instance_ttl(&env);
+
+        publish_event(&env, PauseFlagsUpdatedEvent { flags, treasury });
+
+        Ok(())
+    }
+
+    pub fn get_pause_flags(env: Env) -> u32 {
+        let flags: Option<u32> = env.storage().instance().get(&symbol_short!("pause"));
+        flags.unwrap_or_else(|| {
+            let legacy: Option<ContractState> =
+                env.storage().instance().get(&symbol_short!("state"));
+            if legacy == Some(ContractState::Paused) {
+                PAUSE_ALL
+            } else {
+                0
+            }
+        })
+    }
+
+    /// Pauses operations on escrows in one token only, on top of the global flags.
+    pub fn set_token_pause_flags(env: Env, token: Address, flags: u32) -> Result<(), Error> {
+        let treasury = require_treasury(&env)?;
+        if flags & !PAUSE_ALL != 0 {
+            return Err(Error::InvalidFeeConfiguration);
+        }
+
+        let key = get_token_pause_key(&token);
+        if flags == 0 {
+            env.storage().persistent().remove(&key);
+        } else {
+            save(&env, &key, &flags);
+        }
+
+        publish_event(
+            &env,
+            TokenPauseUpdatedEvent {
+                token,
+                flags,
+                treasury,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_token_pause_flags(env: Env, token: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&get_token_pause_key(&token))
+            .unwrap_or(0)
+    }
+
+    /// Emergency withdraw-only mode. While on, every operation is refused
+    /// except the exits back to depositors, which stay open regardless of
+    /// the global and per-token flags:
+    ///
+    /// - `cancel_escrow` for unfunded escrows and funded ones with nothing released,
+    /// - `cancel_subscription` for future periods,
+    /// - `refund_expired` once the deadline has passed,
+    /// - `refund_hashlock` once the timelock has passed.
+    ///
+    /// Disputes can still be wound down: `withdraw_dispute` returns the escrow
+    /// to its previous status and `execute_ruling` pays out a ruling once its
+    /// appeal window has lapsed. Raising, settling, ruling on and appealing
+    /// disputes wait until the mode is lifted.
+    pub fn set_emergency_mode(env: Env, enabled: bool) -> Result<(), Error> {
+        let treasury = require_treasury(&env)?;
+
+        env.storage()
+            .instance()
+            .set(&symbol_short!("emergency"), &enabled);
+        bump_instance_ttl(&env);
+
+        publish_event(&env, EmergencyModeChangedEvent { enabled, treasury });
+
+        Ok(())
+    }
+
+    pub fn is_emergency_mode(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("emergency"))
+            .unwrap_or(false)
+    }
+
+    pub fn init(env: Env, admin: Address) -> Result<(), Error> {
+        if env.storage().persistent().has(&admin_storage_key()) {
+            return Err(Error::AlreadyInitialized);
+        }
+
+        admin.require_auth();
+        save(&env, &admin_storage_key(), &admin);
+
+        publish_event(
+            &env,
+            RoleUpdatedEvent {
+                role: Symbol::new(&env, "Admin"),
+                address: admin,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Same as `create_escrow`, also attaching integrator metadata.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_escrow_with_metadata(
+        env: Env,
+        escrow_id: u64,
+        depositor: Address,
+        recipient: Address,
+        token_address: Address,
+        milestones: Vec<Milestone>,
+        deadline: u64,
+        metadata: EscrowMetadata,
+    ) -> Result<(), Error> {
+        validate_metadata(&metadata)?;
+        Self::create_escrow(
+            env.clone(),
+            escrow_id,
+            depositor.clone(),
+            recipient,
+            token_address,
+            milestones,
+            deadline,
+        )?;
+        store_metadata(&env, escrow_id, &depositor, &metadata);
+
+        publish_escrow_event(
+            &env,
+            escrow_id,
+            MetadataUpdatedEvent {
+                caller: depositor,
+                external_ref: metadata.external_ref,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Replaces an escrow's metadata. Either party may update it at any time.
+    pub fn update_escrow_metadata(
+        env: Env,
+        escrow_id: u64,
+        caller: Address,
+        metadata: EscrowMetadata,
+    ) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        if caller != escrow.depositor && caller != escrow.recipient {
+            return Err(Error::UnauthorizedAccess);
+        }
+        caller.require_auth();
+        validate_metadata(&metadata)?;
+
+        if let Some(previous) = Self::get_escrow_metadata(env.clone(), escrow_id) {
+            if previous.external_ref.len() > 0 {
+                remove_from_index(
+                    &env,
+                    &get_external_ref_key(&escrow.depositor, &previous.external_ref),
+                    escrow_id,
+                );
+            }
+        }
+        store_metadata(&env, escrow_id, &escrow.depositor, &metadata);
+
+        publish_escrow_event(
+            &env,
+            escrow_id,
+            MetadataUpdatedEvent {
+                caller,
+                external_ref: metadata.external_ref,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_escrow_metadata(env: Env, escrow_id: u64) -> Option<EscrowMetadata> {
+        env.storage().persistent().get(&get_metadata_key(escrow_id))
+    }
+
+    /// Ids of `depositor`'s escrows carrying `external_ref`, oldest first.
+    /// Scoped to the depositor because anyone can open an escrow under any
+    /// reference, but only with themselves as the depositor.
+    pub fn find_by_external_ref(env: Env, depositor: Address, external_ref: String) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&get_external_ref_key(&depositor, &external_ref))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    pub fn create_escrow(
+        env: Env,
+        escrow_id: u64,
+        depositor: Address,
+        recipient: Address,
+        token_address: Address,
+        milestones: Vec<Milestone>,
+        deadline: u64,
+    ) -> Result<(), Error> {
+        depositor.require_auth();
+        ensure_not_paused(&env, PAUSE_NEW_ESCROWS, &token_address)?;
+
+        open_escrow(
+            &env,
+            escrow_id,
+            depositor,
+            recipient,
+            token_address,
+            milestones,
+            deadline,
+        )
+    }
+
+    /// Registers a reusable milestone layout and returns its id. Shares must add
+    /// up to 100%. Escrows created from a template with a default arbiter have
+    /// their disputes decided by that arbiter alone.
+    pub fn register_template(
+        env: Env,
+        owner: Address,
+        milestones_as_bps: Vec<TemplateMilestone>,
+        default_duration: u64,
+        default_arbiter: Option<Address>,
+    ) -> Result<u64, Error> {
+        owner.require_auth();
+
+        if milestones_as_bps.is_empty() || default_duration == 0 {
+            return Err(Error::InvalidSchedule);
+        }
+        if milestones_as_bps.len() > get_milestone_limit(&env) {
+            return Err(Error::VectorTooLarge);
+        }
+        let mut split: i128 = 0;
+        for milestone in milestones_as_bps.iter() {
+            if milestone.bps == 0 {
+                return Err(Error::InvalidMilestoneAmount);
+            }
+            split += milestone.bps as i128;
+        }
+        if split != BPS_DENOMINATOR {
+            return Err(Error::InvalidMilestoneAmount);
+        }
+
+        let template_id: u64 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("tpl_seq"))
+            .unwrap_or(0)
+            + 1;
+        env.storage()
+            .instance()
+            .set(&symbol_short!("tpl_seq"), &template_id);
+
+        let key = get_template_key(template_id);
+        let template = EscrowTemplate {
+            owner: owner.clone(),
+            milestones: milestones_as_bps,
+            default_duration,
+        };
+        save(&env, &key, &template);
+        if let Some(arbiter) = default_arbiter {
+            let arbiter_key = get_template_arbiter_key(template_id);
+            save(&env, &arbiter_key, &arbiter);
+        }
+
+        publish_event(&env, TemplateRegisteredEvent { template_id, owner });
+
+        Ok(template_id)
+    }
+
+    pub fn get_template(env: Env, template_id: u64) -> Result<EscrowTemplate, Error> {
+        env.storage()
+            .persistent()
+            .get(&get_template_key(template_id))
+            .ok_or(Error::EscrowNotFound)
+    }
+
+    pub fn get_template_arbiter(env: Env, template_id: u64) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&get_template_arbiter_key(template_id))
+    }
+
+    /// Arbiter deciding this escrow's disputes alone, if it was created from a
+    /// template with a default arbiter.
+    pub fn get_escrow_arbiter(env: Env, escrow_id: u64) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&get_escrow_arbiter_key(escrow_id))
+    }
+
+    /// Creates an escrow by sizing a template's milestones against
+    /// `total_amount`, with the deadline `default_duration` from now. The last
+    /// milestone absorbs any rounding remainder. The template's arbiter may not
+    /// be one of the parties.
+    pub fn create_escrow_from_template(
+        env: Env,
+        template_id: u64,
+        escrow_id: u64,
+        depositor: Address,
+        recipient: Address,
+        token_address: Address,
+        total_amount: i128,
+    ) -> Result<(), Error> {
+        depositor.require_auth();
+        ensure_not_paused(&env, PAUSE_NEW_ESCROWS, &token_address)?;
+
+        let template = Self::get_template(env.clone(), template_id)?;
+        let arbiter = Self::get_template_arbiter(env.clone(), template_id);
+        if arbiter == Some(depositor.clone()) || arbiter == Some(recipient.clone()) {
+            return Err(Error::SelfDealing);
+        }
+        let deadline = env
+            .ledger()
+            .timestamp()
+            .checked_add(template.default_duration)
+            .ok_or(Error::InvalidDeadline)?;
+
+        let last = template.milestones.len() - 1;
+        let mut allocated: i128 = 0;
+        let mut milestones = Vec::new(&env);
+        for (i, step) in template.milestones.iter().enumerate() {
+            let amount = if i as u32 == last {
+                total_amount - allocated
+            } else {
+                total_amount
+                    .checked_mul(step.bps as i128)
+                    .ok_or(Error::InvalidMilestoneAmount)?
+                    / BPS_DENOMINATOR
+            };
+            allocated += amount;
+            milestones.push_back(Milestone {
+                amount,
+                status: MilestoneStatus::Pending,
+                description: step.description,
+                due_date: 0,
+                review_period: 0,
+                submitted_at: 0,
+                kind: MilestoneKind::Fixed,
+                claimed: 0,
+                condition: ReleaseCondition::None,
+                payees: Vec::new(&env),
+                approval: ApprovalRule::Depositor,
+                metadata: MilestoneMetadata::empty(&env),
+            });
+        }
+
+        open_escrow(
+            &env,
+            escrow_id,
+            depositor,
+            recipient,
+            token_address,
+            milestones,
+            deadline,
+        )?;
+
+        let storage_key = get_storage_key(escrow_id);
+        let mut escrow = load_escrow(&env, escrow_id)?;
+        escrow.template_id = template_id;
+        save(&env, &storage_key, &escrow);
+        publish_escrow_event(&env, escrow_id, TemplateAppliedEvent { template_id });
+
+        if let Some(arbiter) = arbiter {
+            let arbiter_key = get_escrow_arbiter_key(escrow_id);
+            save(&env, &arbiter_key, &arbiter);
+            publish_escrow_event(&env, escrow_id, ArbiterAssignedEvent { arbiter });
+        }
+
+        Ok(())
+    }
+
+    /// Creates a recurring-payment escrow. The schedule is expanded into one
+    /// `Scheduled` milestone per period, each claimable by the recipient once
+    /// its unlock time has passed. Funded with `deposit_funds` like any escrow.
+    pub fn create_subscription(
+        env: Env,
+        escrow_id: u64,
+        depositor: Address,
+        recipient: Address,
+        token_address: Address,
+        schedule: PaymentSchedule,
+        deadline: u64,
+    ) -> Result<(), Error> {
+        depositor.require_auth();
+        ensure_not_paused(&env, PAUSE_NEW_ESCROWS, &token_address)?;
+
+        let milestones = build_schedule(&env, &schedule)?;
+        open_escrow(
+            &env,
+            escrow_id,
+            depositor,
+            recipient,
+            token_address,
+            milestones,
+            deadline,
+        )?;
+
+        let schedule_key = get_schedule_key(escrow_id);
+        save(&env, &schedule_key, &schedule);
+
+        publish_escrow_event(
+            &env,
+            escrow_id,
+            SubscriptionCreatedEvent {
+                period_amount: schedule.period_amount,
+                periods: schedule.periods,
+                interval: schedule.interval,
+                cliff: schedule.cliff,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_schedule(env: Env, escrow_id: u64) -> Result<PaymentSchedule, Error> {
+        env.storage()
+            .persistent()
+            .get(&get_schedule_key(escrow_id))
+            .ok_or(Error::NotASubscription)
+    }
+
+    /// Depositor stops a subscription. Periods unlocking after the notice period
+    /// are cancelled and refunded, less the platform fee; earlier periods remain
+    /// claimable by the recipient. Periods are cancelled latest first, at most
+    /// `SWEEP_PAGE` per call, and dropped from the stored schedule, so a long
+    /// schedule may take several calls.
+    pub fn cancel_subscription(env: Env, escrow_id: u64) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        require_party_auth(&env, escrow_id, Party::Depositor, &escrow.depositor)?;
+        execute_cancel_subscription(env, escrow_id)
+    }
+
+    pub fn deposit_funds(env: Env, escrow_id: u64) -> Result<(), Error> {
+        let storage_key = get_storage_key(escrow_id);
+        let escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        ensure_not_paused(&env, PAUSE_DEPOSITS, &escrow.token_address)?;
+        escrow.depositor.require_auth();
+
+        if escrow.status != EscrowStatus::Created {
+            return Err(Error::EscrowAlreadyFunded);
+        }
+
+        fund_escrow(&env, escrow_id, escrow)
+    }
+
+    /// Recipient asks the named depositor to open and fund an escrow, like
+    /// sending an invoice. Returns the request id. The request lapses after
+    /// `REQUEST_VALIDITY` seconds, or at `deadline` if that comes first.
+    /// Each recipient can have at most `MAX_OPEN_REQUESTS` open requests;
+    /// lapsed ones are cleared to make room before a new one is refused.
+    /// Depositors are not capped, so no sender can crowd others out.
+    pub fn request_escrow(
+        env: Env,
+        recipient: Address,
+        depositor: Address,
+        token_address: Address,
+        milestones: Vec<Milestone>,
+        deadline: u64,
+    ) -> Result<u64, Error> {
+        recipient.require_auth();
+        ensure_not_paused(&env, PAUSE_NEW_ESCROWS, &token_address)?;
+
+        if depositor == recipient {
+            return Err(Error::SelfDealing);
+        }
+        validate_milestones(&env, &milestones, deadline)?;
+
+        let now = env.ledger().timestamp();
+        let expires_at = now.saturating_add(REQUEST_VALIDITY).min(deadline);
+        if expires_at <= now {
+            return Err(Error::InvalidDeadline);
+        }
+
+        ensure_request_capacity(&env, &recipient)?;
+
+        let request_id: u64 = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("req_seq"))
+            .unwrap_or(0)
+            + 1;
+        env.storage()
+            .instance()
+            .set(&symbol_short!("req_seq"), &request_id);
+
+        let request = EscrowRequest {
+            depositor: depositor.clone(),
+            recipient: recipient.clone(),
+            token_address,
+            milestones,
+            deadline,
+            expires_at,
+        };
+        let key = get_request_key(request_id);
+        save(&env, &key, &request);
+        add_to_index(&env, &get_request_outbox_key(&recipient), request_id);
+        add_to_paged_index(&env, symbol_short!("requests"), &depositor, request_id);
+        add_to_paged_index(&env, symbol_short!("requests"), &recipient, request_id);
+
+        publish_event(
+            &env,
+            EscrowRequestedEvent {
+                request_id,
+                depositor,
+                recipient,
+                expires_at,
+            },
+        );
+
+        Ok(request_id)
+    }
+
+    pub fn get_escrow_request(env: Env, request_id: u64) -> Result<EscrowRequest, Error> {
+        env.storage()
+            .persistent()
+            .get(&get_request_key(request_id))
+            .ok_or(Error::EscrowNotFound)
+    }
+
+    /// Ids of the open requests the address has sent or received, oldest
+    /// first, in pages of `INDEX_PAGE`. Lapsed requests stay listed until
+    /// either side clears them.
+    pub fn get_requests_by_party(env: Env, party: Address, page: u32) -> Vec<u64> {
+        index_page(&env, symbol_short!("requests"), &party, page)
+    }
+
+    /// Number of pages in the address's request index.
+    pub fn get_request_index_pages(env: Env, party: Address) -> u32 {
+        index_pages(&env, symbol_short!("requests"), &party)
+    }
+
+    /// Depositor accepts a request, opening it as `escrow_id` and funding it
+    /// in the same call.
+    pub fn accept_escrow_request(env: Env, request_id: u64, escrow_id: u64) -> Result<(), Error> {
+        let request = Self::get_escrow_request(env.clone(), request_id)?;
+        request.depositor.require_auth();
+        ensure_not_paused(
+            &env,
+            PAUSE_NEW_ESCROWS | PAUSE_DEPOSITS,
+            &request.token_address,
+        )?;
+        if env.ledger().timestamp() >= request.expires_at {
+            return Err(Error::InvalidDeadline);
+        }
+
+        open_escrow(
+            &env,
+            escrow_id,
+            request.depositor.clone(),
+            request.recipient.clone(),
+            request.token_address,
+            request.milestones,
+            request.deadline,
+        )?;
+        close_request(&env, request_id, &request.depositor, &request.recipient);
+
+        publish_event(
+            &env,
+            EscrowRequestAcceptedEvent {
+                request_id,
+                escrow_id,
+            },
+        );
+
+        let escrow = load_escrow(&env, escrow_id)?;
+        fund_escrow(&env, escrow_id, escrow)
+    }
+
+    /// Depositor declines a request. Also clears requests that have lapsed.
+    pub fn reject_escrow_request(env: Env, request_id: u64) -> Result<(), Error> {
+        let request = Self::get_escrow_request(env.clone(), request_id)?;
+        request.depositor.require_auth();
+
+        close_request(&env, request_id, &request.depositor, &request.recipient);
+
+        publish_event(
+            &env,
+            EscrowRequestRejectedEvent {
+                request_id,
+                depositor: request.depositor,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Recipient withdraws a request they sent. Also clears requests that have lapsed.
+    pub fn cancel_escrow_request(env: Env, request_id: u64) -> Result<(), Error> {
+        let request = Self::get_escrow_request(env.clone(), request_id)?;
+        request.recipient.require_auth();
+
+        close_request(&env, request_id, &request.depositor, &request.recipient);
+
+        publish_event(
+            &env,
+            EscrowRequestCancelledEvent {
+                request_id,
+                recipient: request.recipient,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Returns the escrow record. Once archived, a stand-in built from the
+    /// receipt is returned with status `Archived`; see `get_escrow_receipt`.
+    pub fn get_escrow(env: Env, escrow_id: u64) -> Result<Escrow, Error> {
+        if let Some(escrow) = env.storage().persistent().get(&get_storage_key(escrow_id)) {
+            return Ok(escrow);
+        }
+        let receipt =
+            Self::get_escrow_receipt(env.clone(), escrow_id).ok_or(Error::EscrowNotFound)?;
+        Ok(Escrow {
+            depositor: receipt.depositor,
+            recipient: receipt.recipient,
+            token_address: receipt.token_address,
+            total_amount: receipt.total_amount,
+            total_released: receipt.total_released,
+            total_refunded: receipt.total_refunded,
+            milestone_count: 0,
+            open_milestones: 0,
+            status: EscrowStatus::Archived,
+            deadline: 0,
+            resolution: receipt.resolution,
+            assignment_consent: false,
+            template_id: 0,
+            hashlock_until: 0,
+        })
+    }
+
+    pub fn get_escrow_receipt(env: Env, escrow_id: u64) -> Option<EscrowReceipt> {
+        env.storage().persistent().get(&get_receipt_key(escrow_id))
+    }
+
+    /// Returns the escrow's event count and running hash. Off-chain replicas
+    /// that replay every event in order must arrive at the same pair.
+    pub fn get_escrow_checkpoint(env: Env, escrow_id: u64) -> Result<EscrowCheckpoint, Error> {
+        Self::get_escrow(env.clone(), escrow_id)?;
+        Ok(load_checkpoint(&env, escrow_id))
+    }
+
+    /// Replaces a closed escrow's storage with a compact receipt. Anyone may
+    /// call this once the escrow is Completed, Cancelled, Expired or Resolved
+    /// and its milestones are all closed. Milestone entries beyond the first
+    /// `SWEEP_PAGE` are removed by `sweep_milestones`.
+    pub fn archive_escrow(env: Env, escrow_id: u64) -> Result<EscrowReceipt, Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        let closed = matches!(
+            escrow.status,
+            EscrowStatus::Completed
+                | EscrowStatus::Cancelled
+                | EscrowStatus::Expired
+                | EscrowStatus::Resolved
+        );
+        if !closed
+            || escrow.open_milestones > 0
+            || Self::get_milestone_sweep(env.clone(), escrow_id).is_some()
+        {
+            return Err(Error::InvalidEscrowStatus);
+        }
+
+        let record = (escrow.clone(), load_checkpoint(&env, escrow_id)).to_xdr(&env);
+        let receipt = EscrowReceipt {
+            depositor: escrow.depositor.clone(),
+            recipient: escrow.recipient.clone(),
+            token_address: escrow.token_address.clone(),
+            total_amount: escrow.total_amount,
+            total_released: escrow.total_released,
+            total_refunded: escrow.total_refunded,
+            final_status: escrow.status,
+            resolution: escrow.resolution,
+            record_hash: env.crypto().sha256(&record),
+            archived_at: env.ledger().timestamp(),
+        };
+
+        remove_escrow_entries(&env, escrow_id);
+        save(&env, &get_receipt_key(escrow_id), &receipt);
+
+        publish_escrow_event(
+            &env,
+            escrow_id,
+            EscrowArchivedEvent {
+                final_status: receipt.final_status,
+                record_hash: receipt.record_hash.clone(),
+            },
+        );
+
+        let sweep = MilestoneSweep {
+            action: SweepAction::Purge,
+            next: 0,
+            end: escrow.milestone_count,
+            refunded: 0,
+        };
+        purge_page(&env, escrow_id, sweep);
+
+        Ok(receipt)
+    }
+
+    /// Carries on milestone work that did not fit in the call that started
+    /// it: restoring milestones after a withdrawn dispute, closing them out,
+    /// or removing them after archiving. Each call handles up to `SWEEP_PAGE`
+    /// milestones and returns how many are left. Callable by anyone; the
+    /// pause flags do not apply, as it only finishes what an allowed call began.
+    pub fn sweep_milestones(env: Env, escrow_id: u64) -> Result<u32, Error> {
+        let sweep =
+            Self::get_milestone_sweep(env.clone(), escrow_id).ok_or(Error::InvalidEscrowStatus)?;
+        if sweep.action == SweepAction::Purge {
+            return Ok(purge_page(&env, escrow_id, sweep));
+        }
+
+        let mut escrow = load_escrow(&env, escrow_id)?;
+        let left = sweep_page(&env, escrow_id, &mut escrow, sweep)?;
+        save(&env, &get_storage_key(escrow_id), &escrow);
+        Ok(left)
+    }
+
+    pub fn get_milestone_sweep(env: Env, escrow_id: u64) -> Option<MilestoneSweep> {
+        env.storage().persistent().get(&get_sweep_key(escrow_id))
+    }
+
+    pub fn get_milestone(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Result<Milestone, Error> {
+        load_escrow(&env, escrow_id)?;
+        load_milestone(&env, escrow_id, milestone_index)
+    }
+
+    /// Returns up to `limit` milestones starting at index `start`.
+    pub fn get_milestones(
+        env: Env,
+        escrow_id: u64,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<Milestone>, Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        let end = start.saturating_add(limit).min(escrow.milestone_count);
+        let mut milestones = Vec::new(&env);
+        for index in start..end {
+            milestones.push_back(load_milestone(&env, escrow_id, index)?);
+        }
+        Ok(milestones)
+    }
+
+    /// Sets how many milestones a new escrow may have. Admin only.
+    pub fn set_milestone_limit(env: Env, limit: u32) -> Result<(), Error> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        if limit == 0 {
+            return Err(Error::ZeroAmount);
+        }
+        if limit > MAX_MILESTONE_LIMIT {
+            return Err(Error::VectorTooLarge);
+        }
+        let old_limit = get_milestone_limit(&env);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("ms_limit"), &limit);
+        bump_instance_ttl(&env);
+
+        publish_event(
+            &env,
+            MilestoneLimitUpdatedEvent {
+                old_limit,
+                new_limit: limit,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_milestone_limit(env: Env) -> u32 {
+        get_milestone_limit(&env)
+    }
+
+    /// Ids of the escrows the address is depositor or recipient of, oldest
+    /// first, in pages of `INDEX_PAGE`. A page loses the ids assigned away
+    /// from the address, so it may hold fewer; see `get_party_index_pages`.
+    pub fn get_escrows_by_party(env: Env, party: Address, page: u32) -> Vec<u64> {
+        index_page(&env, symbol_short!("parties"), &party, page)
+    }
+
+    /// Number of pages in the address's party index.
+    pub fn get_party_index_pages(env: Env, party: Address) -> u32 {
+        index_pages(&env, symbol_short!("parties"), &party)
+    }
+
+    /// Makes `assign_recipient` require the depositor's signature as well as
+    /// the recipient's. Set by the depositor before the escrow is funded.
+    pub fn set_assignment_consent(env: Env, escrow_id: u64, required: bool) -> Result<(), Error> {
+        let storage_key = get_storage_key(escrow_id);
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        escrow.depositor.require_auth();
+
+        if escrow.status != EscrowStatus::Created {
+            return Err(Error::InvalidEscrowStatus);
+        }
+
+        escrow.assignment_consent = required;
+        save(&env, &storage_key, &escrow);
+
+        publish_escrow_event(&env, escrow_id, AssignmentConsentSetEvent { required });
+
+        Ok(())
+    }
+
+    /// Transfers the recipient position, and with it every future payout, to
+    /// `new_recipient`. Milestones and any open dispute carry over unchanged,
+    /// except that a dispute or bond raised by the old recipient now belongs
+    /// to the new one. A pending settlement proposal is dropped. Milestones
+    /// with explicit payees keep paying those payees. A recipient with a
+    /// signer set assigns through `approve_action`; the set and its pending
+    /// approvals are dropped, so the new recipient signs with their own key.
+    pub fn assign_recipient(env: Env, escrow_id: u64, new_recipient: Address) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        require_party_auth(&env, escrow_id, Party::Recipient, &escrow.recipient)?;
+        if escrow.assignment_consent {
+            require_party_auth(&env, escrow_id, Party::Depositor, &escrow.depositor)?;
+        }
+        execute_assign_recipient(env, escrow_id, new_recipient)
+    }
+
+    /// Extends the TTL of an escrow and every entry that belongs to it, apart
+    /// from its milestones; see `bump_milestones`. Anyone may call this to keep
+    /// a long-running escrow from being archived.
+    pub fn bump_escrow(env: Env, escrow_id: u64) -> Result<(), Error> {
+        let receipt_key = get_receipt_key(escrow_id);
+        extend_if_present(&env, &get_checkpoint_key(escrow_id));
+        extend_if_present(&env, &get_sweep_key(escrow_id));
+        if env.storage().persistent().has(&receipt_key) {
+            extend_if_present(&env, &receipt_key);
+            bump_instance_ttl(&env);
+            return Ok(());
+        }
+        load_escrow(&env, escrow_id)?;
+
+        extend_if_present(&env, &get_storage_key(escrow_id));
+        extend_if_present(&env, &get_dispute_key(escrow_id));
+        extend_if_present(&env, &get_settlement_key(escrow_id));
+        extend_if_present(&env, &get_schedule_key(escrow_id));
+        extend_if_present(&env, &get_metadata_key(escrow_id));
+        extend_if_present(&env, &get_escrow_arbiter_key(escrow_id));
+        extend_if_present(&env, &get_signers_key(escrow_id, Party::Depositor));
+        extend_if_present(&env, &get_signers_key(escrow_id, Party::Recipient));
+        for (party, action) in pending_approval_keys(&env, escrow_id).iter() {
+            extend_if_present(&env, &get_approvals_key(escrow_id, party, action));
+        }
+        extend_if_present(&env, &get_pending_index_key(escrow_id));
+        bump_instance_ttl(&env);
+        Ok(())
+    }
+
+    /// Extends the TTL of up to `limit` milestones, and their delivery proofs,
+    /// starting at index `start`.
+    pub fn bump_milestones(env: Env, escrow_id: u64, start: u32, limit: u32) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        let end = start.saturating_add(limit).min(escrow.milestone_count);
+        for index in start..end {
+            extend_if_present(&env, &get_milestone_key(escrow_id, index));
+            extend_if_present(&env, &get_proof_key(escrow_id, index));
+        }
+        bump_instance_ttl(&env);
+        Ok(())
+    }
+
+    /// Extends the TTL of the contract instance (configuration and code).
+    pub fn bump_instance(env: Env) {
+        bump_instance_ttl(&env);
+    }
+
+    pub fn get_state(env: Env, escrow_id: u64) -> Result<EscrowStatus, Error> {
+        let escrow = Self::get_escrow(env, escrow_id)?;
+        Ok(escrow.status)
+    }
+
+    /// Replaces a party's single key with an M-of-N signer set. Only the
+    /// party itself can do this, and only before the escrow is funded.
+    pub fn set_party_signers(
+        env: Env,
+        escrow_id: u64,
+        party: Party,
+        signers: Vec<Address>,
+        threshold: u32,
+    ) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        let owner = match party {
+            Party::Depositor => escrow.depositor,
+            Party::Recipient => escrow.recipient,
+        };
+        owner.require_auth();
+
+        if escrow.status != EscrowStatus::Created {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        if signers.len() > MAX_SIGNERS {
+            return Err(Error::VectorTooLarge);
+        }
+        if threshold == 0 || threshold > signers.len() {
+            return Err(Error::InvalidSignerSet);
+        }
+        for (i, signer) in signers.iter().enumerate() {
+            for other in signers.iter().skip(i + 1) {
+                if signer == other {
+                    return Err(Error::InvalidSignerSet);
+                }
+            }
+        }
+
+        let key = get_signers_key(escrow_id, party);
+        save(&env, &key, &SignerSet { signers, threshold });
+
+        publish_escrow_event(&env, escrow_id, SignersConfiguredEvent { party, threshold });
+
+        Ok(())
+    }
+
+    pub fn get_party_signers(env: Env, escrow_id: u64, party: Party) -> Option<SignerSet> {
+        env.storage()
+            .persistent()
+            .get(&get_signers_key(escrow_id, party))
+    }
+
+    pub fn get_pending_approvals(
+        env: Env,
+        escrow_id: u64,
+        party: Party,
+        action: PartyAction,
+    ) -> Vec<Address> {
+        env.storage()
+            .persistent()
+            .get(&get_approvals_key(escrow_id, party, action))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Records one signer's approval of an action on behalf of their party.
+    /// The approval that reaches the threshold executes the action; for
+    /// disputes that signer also posts the bond. Repeat approvals are no-ops.
+    pub fn approve_action(
+        env: Env,
+        escrow_id: u64,
+        signer: Address,
+        party: Party,
+        action: PartyAction,
+    ) -> Result<(), Error> {
+        signer.require_auth();
+        let escrow = load_escrow(&env, escrow_id)?;
+        let operation = match action {
+            PartyAction::ReleaseMilestone(_)
+            | PartyAction::ConfirmDelivery(_)
+            | PartyAction::AssignRecipient(_)
+            | PartyAction::RejectMilestone(_, _) => PAUSE_RELEASES,
+            PartyAction::CancelEscrow
+            | PartyAction::CancelSubscription
+            | PartyAction::RefundExpired => PAUSE_REFUNDS,
+            PartyAction::RaiseDispute
+            | PartyAction::ProposeSettlement(_)
+            | PartyAction::AcceptSettlement(_)
+            | PartyAction::WithdrawDispute
+            | PartyAction::AppealRuling => PAUSE_DISPUTES,
+        };
+        if action == PartyAction::WithdrawDispute {
+            ensure_dispute_exit_open(&env, &escrow.token_address)?;
+        } else {
+            ensure_not_paused(&env, operation, &escrow.token_address)?;
+        }
+
+        let set = Self::get_party_signers(env.clone(), escrow_id, party)
+            .ok_or(Error::InvalidSignerSet)?;
+        if !set.signers.contains(&signer) {
+            return Err(Error::UnauthorizedAccess);
+        }
+        match (party, &action) {
+            (Party::Recipient, PartyAction::ReleaseMilestone(_))
+            | (Party::Recipient, PartyAction::ConfirmDelivery(_))
+            | (Party::Recipient, PartyAction::CancelEscrow)
+            | (Party::Recipient, PartyAction::RejectMilestone(_, _))
+            | (Party::Recipient, PartyAction::CancelSubscription)
+            | (Party::Recipient, PartyAction::RefundExpired)
+            | (Party::Depositor, PartyAction::AssignRecipient(_)) => {
+                return Err(Error::UnauthorizedAccess);
+            }
+            _ => {}
+        }
+
+        let key = get_approvals_key(escrow_id, party, action.clone());
+        let mut approvals =
+            Self::get_pending_approvals(env.clone(), escrow_id, party, action.clone());
+        if approvals.contains(&signer) {
+            return Ok(());
+        }
+        approvals.push_back(signer.clone());
+
+        publish_escrow_event(
+            &env,
+            escrow_id,
+            ApprovalRecordedEvent {
+                signer: signer.clone(),
+                approvals: approvals.len(),
+                threshold: set.threshold,
+            },
+        );
+
+        if approvals.len() < set.threshold {
+            if approvals.len() == 1 {
+                track_pending_approval(&env, escrow_id, party, action.clone(), true);
+            }
+            save(&env, &key, &approvals);
+            return Ok(());
+        }
+
+        if approvals.len() > 1 {
+            env.storage().persistent().remove(&key);
+            track_pending_approval(&env, escrow_id, party, action.clone(), false);
+        }
+        let caller = match party {
+            Party::Depositor => escrow.depositor.clone(),
+            Party::Recipient => escrow.recipient.clone(),
+        };
+        match action {
+            PartyAction::ReleaseMilestone(index) => {
+                execute_release_milestone(env, escrow_id, index, false)
+            }
+            PartyAction::ConfirmDelivery(index) => execute_confirm_delivery(env, escrow_id, index),
+            PartyAction::CancelEscrow => execute_cancel_escrow(env, escrow_id),
+            PartyAction::RaiseDispute => execute_raise_dispute(env, escrow_id, caller, signer),
+            PartyAction::ProposeSettlement(amount) => {
+                execute_propose_settlement(env, escrow_id, caller, amount)
+            }
+            PartyAction::AcceptSettlement(amount) => {
+                execute_accept_settlement(env, escrow_id, caller, amount)
+            }
+            PartyAction::WithdrawDispute => execute_withdraw_dispute(env, escrow_id, caller),
+            PartyAction::AssignRecipient(new_recipient) => {
+                if escrow.assignment_consent {
+                    require_party_auth(&env, escrow_id, Party::Depositor, &escrow.depositor)?;
+                }
+                execute_assign_recipient(env, escrow_id, new_recipient)
+            }
+            PartyAction::RejectMilestone(index, reason) => {
+                execute_reject_milestone(env, escrow_id, index, reason)
+            }
+            PartyAction::CancelSubscription => execute_cancel_subscription(env, escrow_id),
+            PartyAction::RefundExpired => execute_refund_expired(env, escrow_id),
+            PartyAction::AppealRuling => execute_appeal_ruling(env, escrow_id, caller, signer),
+        }
+    }
+
+    pub fn release_milestone(env: Env, escrow_id: u64, milestone_index: u32) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        require_party_auth(&env, escrow_id, Party::Depositor, &escrow.depositor)?;
+        execute_release_milestone(env, escrow_id, milestone_index, false)
+    }
+
+    /// Releases a milestone on the approver's signature alone. Only milestones
+    /// whose rule is `DepositorOrApprover` can be released this way.
+    pub fn release_by_approver(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Result<(), Error> {
+        let milestone = Self::get_milestone(env.clone(), escrow_id, milestone_index)?;
+        match milestone.approval {
+            ApprovalRule::DepositorOrApprover(approver) => approver.require_auth(),
+            _ => return Err(Error::UnauthorizedAccess),
+        }
+        execute_release_milestone(env, escrow_id, milestone_index, true)
+    }
+
+    /// Recipient submits a milestone for review with a hash of the delivery proof.
+    /// Starts the milestone's review period.
+    pub fn submit_milestone(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        proof_hash: BytesN<32>,
+    ) -> Result<(), Error> {
+        let storage_key = get_storage_key(escrow_id);
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        ensure_not_paused(&env, PAUSE_RELEASES, &escrow.token_address)?;
+        escrow.recipient.require_auth();
+
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+
+        let mut milestone = load_milestone(&env, escrow_id, milestone_index)?;
+        if milestone.status != MilestoneStatus::Pending {
+            return Err(Error::InvalidMilestoneStatus);
+        }
+
+        let submitted_at = env.ledger().timestamp();
+        milestone.status = MilestoneStatus::Submitted;
+        milestone.submitted_at = submitted_at;
+        put_milestone(&env, escrow_id, &mut escrow, milestone_index, &milestone)?;
+
+        let proof_key = get_proof_key(escrow_id, milestone_index);
+        save(&env, &storage_key, &escrow);
+        save(&env, &proof_key, &proof_hash);
+
+        publish_escrow_event(
+            &env,
+            escrow_id,
+            MilestoneSubmittedEvent {
+                milestone_index,
+                proof_hash,
+                submitted_at,
+                due_date: milestone.due_date,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Depositor sends a submitted milestone back to the recipient for rework.
+    pub fn reject_milestone(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        reason: Symbol,
+    ) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        require_party_auth(&env, escrow_id, Party::Depositor, &escrow.depositor)?;
+        execute_reject_milestone(env, escrow_id, milestone_index, reason)
+    }
+
+    /// Recipient pulls whatever a vesting or scheduled milestone has accrued so far.
+    /// Claims are only possible while the escrow is Active, so disputes freeze
+    /// vesting; cancellation and expiry pay out what had accrued by then.
+    /// Milestones whose rule is `DepositorAndApprover` also need the approver's
+    /// signature on every claim.
+    pub fn claim_vested(env: Env, escrow_id: u64, milestone_index: u32) -> Result<(), Error> {
+        let storage_key = get_storage_key(escrow_id);
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        ensure_not_paused(&env, PAUSE_RELEASES, &escrow.token_address)?;
+        escrow.recipient.require_auth();
+
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+
+        let mut milestone = load_milestone(&env, escrow_id, milestone_index)?;
+        if milestone.status != MilestoneStatus::Pending {
+            return Err(Error::InvalidMilestoneStatus);
+        }
+        check_approval(&milestone, false)?;
+
+        let vested = vested_amount(&milestone, env.ledger().timestamp())?;
+        let claimable = vested
+            .checked_sub(milestone.claimed)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        if claimable <= 0 {
+            return Err(Error::NothingToClaim);
+        }
+
+        let (payout, fee) = pay_recipient(&env, &escrow, &milestone, claimable)?;
+
+        milestone.claimed = vested;
+        if milestone.claimed == milestone.amount {
+            milestone.status = MilestoneStatus::Released;
+        }
+        put_milestone(&env, escrow_id, &mut escrow, milestone_index, &milestone)?;
+
+        escrow.total_released = escrow
+            .total_released
+            .checked_add(claimable)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+
+        save(&env, &storage_key, &escrow);
+
+        publish_escrow_event(
+            &env,
+            escrow_id,
+            VestedClaimedEvent {
+                milestone_index,
+                payout,
+                fee,
+                claimed: milestone.claimed,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_milestone_proof(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Result<BytesN<32>, Error> {
+        env.storage()
+            .persistent()
+            .get(&get_proof_key(escrow_id, milestone_index))
+            .ok_or(Error::MilestoneNotDelivered)
+    }
+
+    /// Releases a milestone whose verifier contract reports the delivery as done.
+    /// Callable by anyone; the verifier is asked via `verify(escrow_id, milestone_index)`.
+    /// The verifier stands in for the depositor only, so a `DepositorAndApprover`
+    /// milestone still needs the approver's signature.
+    pub fn release_if_verified(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Result<(), Error> {
+        let storage_key = get_storage_key(escrow_id);
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        ensure_not_paused(&env, PAUSE_RELEASES, &escrow.token_address)?;
+
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+
+        let mut milestone = load_milestone(&env, escrow_id, milestone_index)?;
+        if milestone.status != MilestoneStatus::Pending
+            && milestone.status != MilestoneStatus::Submitted
+        {
+            return Err(Error::InvalidMilestoneStatus);
+        }
+
+        let verifier = match milestone.condition.clone() {
+            ReleaseCondition::Verifier(verifier) => verifier,
+            _ => return Err(Error::ConditionNotMet),
+        };
+        if !VerifierClient::new(&env, &verifier).verify(&escrow_id, &milestone_index) {
+            return Err(Error::ConditionNotMet);
+        }
+        check_approval(&milestone, false)?;
+
+        let remaining = unpaid_amount(&milestone)?;
+        let (payout, fee) = pay_recipient(&env, &escrow, &milestone, remaining)?;
+
+        milestone.status = MilestoneStatus::Released;
+        milestone.claimed = milestone.amount;
+        put_milestone(&env, escrow_id, &mut escrow, milestone_index, &milestone)?;
+
+        escrow.total_released = escrow
+            .total_released
+            .checked_add(remaining)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+
+        save(&env, &storage_key, &escrow);
+
+        publish_escrow_event(
+            &env,
+            escrow_id,
+            MilestoneReleasedEvent {
+                milestone_index,
+                payout,
+                fee,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Releases a hash-locked milestone to the recipient when `preimage` hashes to
+    /// the stored SHA-256 digest. Callable by anyone before the timelock; the
+    /// preimage is published so the counterparty of a swap can use it. A
+    /// `DepositorAndApprover` milestone still needs the approver's signature.
+    pub fn release_with_preimage(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        preimage: Bytes,
+    ) -> Result<(), Error> {
+        let storage_key = get_storage_key(escrow_id);
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        ensure_not_paused(&env, PAUSE_RELEASES, &escrow.token_address)?;
+
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+
+        let mut milestone = load_milestone(&env, escrow_id, milestone_index)?;
+        if milestone.status != MilestoneStatus::Pending
+            && milestone.status != MilestoneStatus::Submitted
+        {
+            return Err(Error::InvalidMilestoneStatus);
+        }
+
+        let (hash, timelock) = match milestone.condition.clone() {
+            ReleaseCondition::HashLock(hash, timelock) => (hash, timelock),
+            _ => return Err(Error::ConditionNotMet),
+        };
+        if env.ledger().timestamp() > timelock {
+            return Err(Error::HashLockExpired);
+        }
+        if env.crypto().sha256(&preimage) != hash {
+            return Err(Error::InvalidPreimage);
+        }
+        check_approval(&milestone, false)?;
+
+        let remaining = unpaid_amount(&milestone)?;
+        let (payout, fee) = pay_recipient(&env, &escrow, &milestone, remaining)?;
+
+        milestone.status = MilestoneStatus::Released;
+        milestone.claimed = milestone.amount;
+        put_milestone(&env, escrow_id, &mut escrow, milestone_index, &milestone)?;
+
+        escrow.total_released = escrow
+            .total_released
+            .checked_add(remaining)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+
+        save(&env, &storage_key, &escrow);
+
+        publish_escrow_event(
+            &env,
+            escrow_id,
+            PreimageRevealedEvent {
+                milestone_index,
+                preimage,
+            },
+        );
+        publish_escrow_event(
+            &env,
+            escrow_id,
+            MilestoneReleasedEvent {
+                milestone_index,
+                payout,
+                fee,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Returns a hash-locked milestone to the depositor once its timelock has
+    /// passed without the preimage being revealed. Callable by anyone; the
+    /// platform fee is retained as with other refunds.
+    pub fn refund_hashlock(env: Env, escrow_id: u64, milestone_index: u32) -> Result<(), Error> {
+        let storage_key = get_storage_key(escrow_id);
+
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        ensure_not_paused(&env, PAUSE_REFUNDS, &escrow.token_address)?;
+
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+
+        let mut milestone = load_milestone(&env, escrow_id, milestone_index)?;
+        if milestone.status != MilestoneStatus::Pending
+            && milestone.status != MilestoneStatus::Submitted
+        {
+            return Err(Error::InvalidMilestoneStatus);
+        }
+
+        let timelock = match milestone.condition {
+            ReleaseCondition::HashLock(_, timelock) => timelock,
+            _ => return Err(Error::ConditionNotMet),
+        };
+        if env.ledger().timestamp() <= timelock {
+            return Err(Error::HashLockActive);
+        }
+
+        let remaining = unpaid_amount(&milestone)?;
+        let (treasury, fee_bps) = Self::get_config(env.clone())?;
+        let fee = calculate_fee(remaining, fee_bps)?;
+        let refund_amount = remaining
+            .checked_sub(fee)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+
+        let token_client = token::Client::new(&env, &escrow.token_address);
+        if refund_amount > 0 {
+            token_client.transfer(
+                &env.current_contract_address(),
+                &escrow.depositor,
+                &refund_amount,
+            );
+        }
+        if fee > 0 {
+            token_client.transfer(&env.current_contract_address(), &treasury, &fee);
+        }
+
+        milestone.status = MilestoneStatus::Refunded;
+        put_milestone(&env, escrow_id, &mut escrow, milestone_index, &milestone)?;
+
+        escrow.total_refunded = escrow
+            .total_refunded
+            .checked_add(remaining)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+
+        save(&env, &storage_key, &escrow);
+
+        publish_escrow_event(
+            &env,
+            escrow_id,
+            HashLockRefundedEvent {
+                milestone_index,
+                refund_amount,
+                fee,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Pays a delivered milestone once its review period has passed without the
+    /// depositor confirming or disputing it. Callable by anyone, but a
+    /// `DepositorAndApprover` milestone still needs the approver's signature.
+    pub fn auto_release_milestone(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+    ) -> Result<(), Error> {
+        let storage_key = get_storage_key(escrow_id);
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        ensure_not_paused(&env, PAUSE_RELEASES, &escrow.token_address)?;
+
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::EscrowNotActive);
+        }
+
+        let mut milestone = load_milestone(&env, escrow_id, milestone_index)?;
+        if milestone.status != MilestoneStatus::Submitted || milestone.review_period == 0 {
+            return Err(Error::MilestoneNotDelivered);
+        }
+
+        let review_ends = milestone
+            .submitted_at
+            .checked_add(milestone.review_period)
+            .ok_or(Error::InvalidDeadline)?;
+        if env.ledger().timestamp() <= review_ends {
+            return Err(Error::ReviewPeriodActive);
+        }
+        check_approval(&milestone, false)?;
+
+        let remaining = unpaid_amount(&milestone)?;
+        let (payout, fee) = pay_recipient(&env, &escrow, &milestone, remaining)?;
+
+        milestone.status = MilestoneStatus::Released;
+        milestone.claimed = milestone.amount;
+        put_milestone(&env, escrow_id, &mut escrow, milestone_index, &milestone)?;
+
+        escrow.total_released = escrow
+            .total_released
+            .checked_add(remaining)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+
+        save(&env, &storage_key, &escrow);
+
+        publish_escrow_event(
+            &env,
+            escrow_id,
+            MilestoneReleasedEvent {
+                milestone_index,
+                payout,
+                fee,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn confirm_delivery(
+        env: Env,
+        escrow_id: u64,
+        milestone_index: u32,
+        buyer: Address,
+    ) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        if escrow.depositor != buyer {
+            return Err(Error::UnauthorizedAccess);
+        }
+        require_party_auth(&env, escrow_id, Party::Depositor, &buyer)?;
+        execute_confirm_delivery(env, escrow_id, milestone_index)
+    }
+
+    /// Either party, or an approver named on any milestone, can raise a dispute.
+    /// The dispute always freezes the whole escrow and the ruling settles every
+    /// open milestone. Payees cannot raise one: they are only owed part of the
+    /// milestones that name them, so they take complaints to the recipient.
+    pub fn raise_dispute(env: Env, escrow_id: u64, caller: Address) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        match party_of(&escrow, &caller) {
+            Some(party) => require_party_auth(&env, escrow_id, party, &caller)?,
+            None if is_milestone_approver(&env, escrow_id, &escrow, &caller)? => {
+                caller.require_auth()
+            }
+            None => return Err(Error::UnauthorizedAccess),
+        }
+        execute_raise_dispute(env, escrow_id, caller.clone(), caller)
+    }
+
+    pub fn resolve_dispute(env: Env, escrow_id: u64, winner: Address) -> Result<(), Error> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        let escrow = load_escrow(&env, escrow_id)?;
+        ensure_not_paused(&env, PAUSE_DISPUTES, &escrow.token_address)?;
+
+        if escrow.status != EscrowStatus::Disputed {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        if winner != escrow.depositor && winner != escrow.recipient {
+            return Err(Error::InvalidWinner);
+        }
+
+        let dispute = Self::get_dispute(env.clone(), escrow_id)?;
+        if dispute.quorum > 0 {
+            return Err(Error::PanelRequired);
+        }
+
+        issue_ruling(
+            &env,
+            escrow_id,
+            escrow,
+            dispute,
+            winner,
+            Vec::from_array(&env, [admin]),
+        )
+    }
+
+    /// Configures the arbitration panel used for escrows whose total amount is at
+    /// least `threshold`. Disputes snapshot the panel when they are raised, so later
+    /// changes only affect new disputes. An empty panel disables panel arbitration.
+    pub fn configure_panel(
+        env: Env,
+        arbiters: Vec<Address>,
+        quorum: u32,
+        threshold: i128,
+    ) -> Result<(), Error> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        if arbiters.len() > MAX_PANEL_SIZE {
+            return Err(Error::VectorTooLarge);
+        }
+        if !arbiters.is_empty() && (quorum == 0 || quorum > arbiters.len() || threshold <= 0) {
+            return Err(Error::InvalidPanelConfiguration);
+        }
+        for (i, arbiter) in arbiters.iter().enumerate() {
+            if arbiters.first_index_of(&arbiter) != Some(i as u32) {
+                return Err(Error::InvalidPanelConfiguration);
+            }
+        }
+
+        let panel = ArbiterPanel {
+            arbiters: arbiters.clone(),
+            quorum,
+            threshold,
+        };
+        save(&env, &symbol_short!("panel"), &panel);
+
+        publish_event(
+            &env,
+            PanelConfiguredEvent {
+                arbiters,
+                quorum,
+                threshold,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_panel(env: Env) -> Option<ArbiterPanel> {
+        env.storage().persistent().get(&symbol_short!("panel"))
+    }
+
+    /// Records a panel member's ruling on a disputed escrow. A member may change
+    /// their vote until a quorum is reached; the first ruling to collect `quorum`
+    /// votes is executed immediately.
+    pub fn cast_arbiter_vote(
+        env: Env,
+        escrow_id: u64,
+        arbiter: Address,
+        ruling: Ruling,
+    ) -> Result<(), Error> {
+        arbiter.require_auth();
+
+        let escrow = load_escrow(&env, escrow_id)?;
+        ensure_not_paused(&env, PAUSE_DISPUTES, &escrow.token_address)?;
+        if escrow.status != EscrowStatus::Disputed {
+            return Err(Error::InvalidEscrowStatus);
+        }
+
+        let dispute_key = get_dispute_key(escrow_id);
+        let mut dispute = Self::get_dispute(env.clone(), escrow_id)?;
+        if !dispute.panel.contains(&arbiter) {
+            return Err(Error::NotPanelMember);
+        }
+
+        let vote = ArbiterVote {
+            arbiter: arbiter.clone(),
+            ruling,
+        };
+        match dispute.votes.iter().position(|v| v.arbiter == arbiter) {
+            Some(i) => dispute.votes.set(i as u32, vote),
+            None => dispute.votes.push_back(vote),
+        }
+        save(&env, &dispute_key, &dispute);
+
+        publish_escrow_event(&env, escrow_id, ArbiterVoteCastEvent { arbiter, ruling });
+
+        if ruling == Ruling::Abstain {
+            return Ok(());
+        }
+
+        let mut majority = Vec::new(&env);
+        for v in dispute.votes.iter() {
+            if v.ruling == ruling {
+                majority.push_back(v.arbiter);
+            }
+        }
+        if majority.len() < dispute.quorum {
+            return Ok(());
+        }
+
+        let winner = if ruling == Ruling::Recipient {
+            escrow.recipient.clone()
+        } else {
+            escrow.depositor.clone()
+        };
+        issue_ruling(&env, escrow_id, escrow, dispute, winner, majority)
+    }
+
+    /// Swaps a member of an open dispute's panel, e.g. for conflict of interest
+    /// or unresponsiveness. Any vote cast by the outgoing member is discarded.
+    pub fn replace_arbiter(
+        env: Env,
+        escrow_id: u64,
+        old_arbiter: Address,
+        new_arbiter: Address,
+    ) -> Result<(), Error> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        let escrow = load_escrow(&env, escrow_id)?;
+        if escrow.status != EscrowStatus::Disputed {
+            return Err(Error::InvalidEscrowStatus);
+        }
+
+        let dispute_key = get_dispute_key(escrow_id);
+        let mut dispute = Self::get_dispute(env.clone(), escrow_id)?;
+        let index = dispute
+            .panel
+            .first_index_of(&old_arbiter)
+            .ok_or(Error::NotPanelMember)?;
+        if dispute.panel.contains(&new_arbiter) {
+            return Err(Error::InvalidPanelConfiguration);
+        }
+
+        dispute.panel.set(index, new_arbiter.clone());
+        if let Some(i) = dispute.votes.iter().position(|v| v.arbiter == old_arbiter) {
+            dispute.votes.remove(i as u32);
+        }
+        save(&env, &dispute_key, &dispute);
+
+        publish_escrow_event(
+            &env,
+            escrow_id,
+            ArbiterReplacedEvent {
+                old_arbiter,
+                new_arbiter,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Configures the appeal period applied to new rulings. While the window is
+    /// open the ruling is recorded but funds stay in escrow; the losing party can
+    /// post `bond` to escalate to `arbiter`. A zero window pays out immediately.
+    pub fn set_appeal_config(
+        env: Env,
+        window: u64,
+        bond: i128,
+        arbiter: Address,
+    ) -> Result<(), Error> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        if bond < 0 {
+            return Err(Error::InvalidBondAmount);
+        }
+
+        let config = AppealConfig {
+            window,
+            bond,
+            arbiter: arbiter.clone(),
+        };
+        save(&env, &symbol_short!("appeal"), &config);
+
+        publish_event(
+            &env,
+            AppealConfigUpdatedEvent {
+                window,
+                bond,
+                arbiter,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_appeal_config(env: Env) -> Option<AppealConfig> {
+        env.storage().persistent().get(&symbol_short!("appeal"))
+    }
+
+    /// Escalates a pending ruling to the secondary arbiter. Only the losing
+    /// party may appeal, and only before the appeal window lapses.
+    pub fn appeal_ruling(env: Env, escrow_id: u64, caller: Address) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        let party = party_of(&escrow, &caller).ok_or(Error::UnauthorizedAccess)?;
+        require_party_auth(&env, escrow_id, party, &caller)?;
+        execute_appeal_ruling(env, escrow_id, caller.clone(), caller)
+    }
+
+    /// Final ruling on an appealed dispute by the secondary arbiter assigned at
+    /// appeal time. Pays out immediately; there is no further appeal.
+    pub fn resolve_appeal(env: Env, escrow_id: u64, winner: Address) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        ensure_not_paused(&env, PAUSE_DISPUTES, &escrow.token_address)?;
+        if escrow.status != EscrowStatus::Appealed {
+            return Err(Error::InvalidEscrowStatus);
+        }
+
+        let dispute = Self::get_dispute(env.clone(), escrow_id)?;
+        let arbiter = dispute
+            .ruling_arbiters
+            .first()
+            .ok_or(Error::AppealsDisabled)?;
+        arbiter.require_auth();
+
+        if winner != escrow.depositor && winner != escrow.recipient {
+            return Err(Error::InvalidWinner);
+        }
+
+        let arbiters = dispute.ruling_arbiters.clone();
+        apply_ruling(&env, escrow_id, escrow, &dispute, winner, &arbiters)
+    }
+
+    /// Pays out a ruling whose appeal window lapsed without an appeal.
+    /// Callable by anyone.
+    pub fn execute_ruling(env: Env, escrow_id: u64) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        ensure_dispute_exit_open(&env, &escrow.token_address)?;
+        if escrow.status != EscrowStatus::RulingPending {
+            return Err(Error::InvalidEscrowStatus);
+        }
+
+        let dispute = Self::get_dispute(env.clone(), escrow_id)?;
+        let winner = ruling_winner(&escrow, dispute.ruling)?;
+        if env.ledger().timestamp() <= dispute.appeal_deadline {
+            return Err(Error::AppealWindowOpen);
+        }
+
+        let arbiters = dispute.ruling_arbiters.clone();
+        apply_ruling(&env, escrow_id, escrow, &dispute, winner, &arbiters)
+    }
+
+    /// Sets the bond a party must post in `token_address` to raise a dispute.
+    /// A zero amount disables the bond for that token.
+    pub fn set_dispute_bond(env: Env, token_address: Address, amount: i128) -> Result<(), Error> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        if amount < 0 {
+            return Err(Error::InvalidBondAmount);
+        }
+
+        let bond_key = get_bond_key(token_address.clone());
+        let old_amount: i128 = env.storage().persistent().get(&bond_key).unwrap_or(0);
+        save(&env, &bond_key, &amount);
+
+        publish_event(
+            &env,
+            DisputeBondUpdatedEvent {
+                token_address,
+                old_amount,
+                new_amount: amount,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_dispute_bond(env: Env, token_address: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&get_bond_key(token_address))
+            .unwrap_or(0)
+    }
+
+    /// Sets the share of the disputed balance, in bps, paid to the arbiter on resolution.
+    pub fn set_arbiter_fee(env: Env, fee_bps: i128) -> Result<(), Error> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        if !(0..=BPS_DENOMINATOR).contains(&fee_bps) {
+            return Err(Error::InvalidFeeConfiguration);
+        }
+
+        let old_fee = get_arbiter_fee_bps(&env);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("arb_fee"), &fee_bps);
+        bump_instance_ttl(&env);
+
+        publish_event(
+            &env,
+            FeeUpdatedEvent {
+                fee: Symbol::new(&env, "ArbiterFee"),
+                old_bps: old_fee,
+                new_bps: fee_bps,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn get_dispute(env: Env, escrow_id: u64) -> Result<Dispute, Error> {
+        env.storage()
+            .persistent()
+            .get(&get_dispute_key(escrow_id))
+            .ok_or(Error::DisputeNotFound)
+    }
+
+    /// Offers to close a dispute without the arbiter: `recipient_amount` of the
+    /// outstanding balance goes to the recipient and the rest back to the depositor.
+    /// A new proposal from either party replaces the previous one.
+    pub fn propose_settlement(
+        env: Env,
+        escrow_id: u64,
+        caller: Address,
+        recipient_amount: i128,
+    ) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        let party = party_of(&escrow, &caller).ok_or(Error::UnauthorizedAccess)?;
+        require_party_auth(&env, escrow_id, party, &caller)?;
+        execute_propose_settlement(env, escrow_id, caller, recipient_amount)
+    }
+
+    /// Accepts the counterparty's settlement proposal and pays out the agreed split.
+    /// `recipient_amount` must match the stored proposal so a proposal swapped in
+    /// before this call lands cannot be accepted by accident.
+    pub fn accept_settlement(
+        env: Env,
+        escrow_id: u64,
+        caller: Address,
+        recipient_amount: i128,
+    ) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        let party = party_of(&escrow, &caller).ok_or(Error::UnauthorizedAccess)?;
+        require_party_auth(&env, escrow_id, party, &caller)?;
+        execute_accept_settlement(env, escrow_id, caller, recipient_amount)
+    }
+
+    /// Lets the party that raised the dispute drop it, returning the escrow
+    /// to the status it had before `raise_dispute`.
+    pub fn withdraw_dispute(env: Env, escrow_id: u64, caller: Address) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        match party_of(&escrow, &caller) {
+            Some(party) => require_party_auth(&env, escrow_id, party, &caller)?,
+            None => caller.require_auth(),
+        }
+        execute_withdraw_dispute(env, escrow_id, caller)
+    }
+
+    /// Cancels an escrow before anything was released, refunding whatever it
+    /// holds. A funded escrow cannot be cancelled while an open hash-locked
+    /// milestone is still within its timelock, as the recipient may already
+    /// have been handed the preimage. Funded subscriptions are stopped with
+    /// `cancel_subscription` instead.
+    pub fn cancel_escrow(env: Env, escrow_id: u64) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        require_party_auth(&env, escrow_id, Party::Depositor, &escrow.depositor)?;
+        execute_cancel_escrow(env, escrow_id)
+    }
+
+    pub fn complete_escrow(env: Env, escrow_id: u64) -> Result<(), Error> {
+        let storage_key = get_storage_key(escrow_id);
+        let mut escrow: Escrow = env
+            .storage()
+            .persistent()
+            .get(&storage_key)
+            .ok_or(Error::EscrowNotFound)?;
+        ensure_not_paused(&env, PAUSE_RELEASES, &escrow.token_address)?;
+        escrow.depositor.require_auth();
+
+        if escrow.status != EscrowStatus::Active {
+            return Err(Error::InvalidEscrowStatus);
+        }
+        if escrow.open_milestones > 0 {
+            return Err(Error::EscrowNotActive);
+        }
+
+        escrow.status = EscrowStatus::Completed;
+        ensure_milestones_terminal(&escrow)?;
+        save(&env, &storage_key, &escrow);
+
+        // Standardized Event
+        publish_escrow_event(
+            &env,
+            escrow_id,
+            EscrowCompletedEvent {
+                total_released: escrow.total_released,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Depositor reclaims an active escrow once its deadline has passed.
+    /// Vesting and scheduled milestones first pay the recipient what they had
+    /// accrued by then.
+    pub fn refund_expired(env: Env, escrow_id: u64, caller: Address) -> Result<(), Error> {
+        let escrow = load_escrow(&env, escrow_id)?;
+        // Only the buyer can refund
+        if caller != escrow.depositor {
+            return Err(Error::Unauthorized);
+        }
+        require_party_auth(&env, escrow_id, Party::Depositor, &caller)?;
+        execute_refund_expired(env, escrow_id)
+    }
+}
+
+fn get_storage_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("escrow"), escrow_id)
+}
+
+fn get_receipt_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("receipt"), escrow_id)
+}
+
+fn get_checkpoint_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("chain"), escrow_id)
+}
+
+fn load_checkpoint(env: &Env, escrow_id: u64) -> EscrowCheckpoint {
+    env.storage()
+        .persistent()
+        .get(&get_checkpoint_key(escrow_id))
+        .unwrap_or(EscrowCheckpoint {
+            sequence: 0,
+            hash: BytesN::from_array(env, &[0; 32]),
+        })
+}
+
+/// Publishes an event about the contract as a whole.
+fn publish_event<E: ContractEvent>(env: &Env, event: E) {
+    let topics = (
+        Symbol::new(env, "Vaultix"),
+        Symbol::new(env, E::NAME),
+        EVENT_VERSION,
+    );
+    let event: Val = event.into_val(env);
+    env.events().publish(topics, event);
+}
+
+/// Publishes an event about an escrow, stamped with the escrow's next sequence
+/// number, and folds it into the escrow's hash chain so the chain covers
+/// exactly what indexers observe.
+fn publish_escrow_event<E: EscrowEvent>(env: &Env, escrow_id: u64, event: E) {
+    let topics = (
+        Symbol::new(env, "Vaultix"),
+        Symbol::new(env, E::NAME),
+        EVENT_VERSION,
+        escrow_id,
+    );
+    let checkpoint = load_checkpoint(env, escrow_id);
+    let sequence = checkpoint.sequence + 1;
+    let event: Val = event.into_val(env);
+    let data: Val = (sequence, event).into_val(env);
+
+    let mut preimage: Bytes = checkpoint.hash.into();
+    preimage.append(&(topics.clone(), data).to_xdr(env));
+    let next = EscrowCheckpoint {
+        sequence,
+        hash: env.crypto().sha256(&preimage),
+    };
+    save(env, &get_checkpoint_key(escrow_id), &next);
+
+    env.events().publish(topics, data);
+}
+
+fn load_escrow(env: &Env, escrow_id: u64) -> Result<Escrow, Error> {
+    env.storage()
+        .persistent()
+        .get(&get_storage_key(escrow_id))
+        .ok_or(Error::EscrowNotFound)
+}
+
+/// Removes an escrow's header and every entry keyed by it apart from the
+/// milestones, which `purge_page` removes a page at a time. Party and
+/// external-reference indexes and the hash chain are kept with the receipt.
+fn remove_escrow_entries(env: &Env, escrow_id: u64) {
+    let storage = env.storage().persistent();
+    for (party, action) in pending_approval_keys(env, escrow_id).iter() {
+        storage.remove(&get_approvals_key(escrow_id, party, action));
+    }
+    storage.remove(&get_pending_index_key(escrow_id));
+    for party in [Party::Depositor, Party::Recipient] {
+        storage.remove(&get_signers_key(escrow_id, party));
+    }
+    storage.remove(&get_dispute_key(escrow_id));
+    storage.remove(&get_settlement_key(escrow_id));
+    storage.remove(&get_schedule_key(escrow_id));
+    storage.remove(&get_metadata_key(escrow_id));
+    storage.remove(&get_escrow_arbiter_key(escrow_id));
+    storage.remove(&get_storage_key(escrow_id));
+}
+
+fn get_schedule_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("sched"), escrow_id)
+}
+
+fn get_sweep_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("sweep"), escrow_id)
+}
+
+fn get_milestone_limit(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&symbol_short!("ms_limit"))
+        .unwrap_or(DEFAULT_MILESTONE_LIMIT)
+}
+
+fn get_proof_key(escrow_id: u64, milestone_index: u32) -> (Symbol, u64, u32) {
+    (symbol_short!("proof"), escrow_id, milestone_index)
+}
+
+fn get_dispute_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("dispute"), escrow_id)
+}
+
+fn get_bond_key(token_address: Address) -> (Symbol, Address) {
+    (symbol_short!("bond"), token_address)
+}
+
+fn get_arbiter_fee_bps(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&symbol_short!("arb_fee"))
+        .unwrap_or(0)
+}
+
+fn get_settlement_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("settle"), escrow_id)
+}
+
+fn get_signers_key(escrow_id: u64, party: Party) -> (Symbol, u64, Party) {
+    (symbol_short!("signers"), escrow_id, party)
+}
+
+fn get_approvals_key(
+    escrow_id: u64,
+    party: Party,
+    action: PartyAction,
+) -> (Symbol, u64, Party, PartyAction) {
+    (symbol_short!("approve"), escrow_id, party, action)
+}
+
+fn get_pending_index_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("pending"), escrow_id)
+}
+
+/// Approval entries currently stored for an escrow, so archiving can remove
+/// exactly those instead of probing every possible action.
+fn pending_approval_keys(env: &Env, escrow_id: u64) -> Vec<(Party, PartyAction)> {
+    env.storage()
+        .persistent()
+        .get(&get_pending_index_key(escrow_id))
+        .unwrap_or(Vec::new(env))
+}
+
+fn track_pending_approval(
+    env: &Env,
+    escrow_id: u64,
+    party: Party,
+    action: PartyAction,
+    pending: bool,
+) {
+    let key = get_pending_index_key(escrow_id);
+    let mut index = pending_approval_keys(env, escrow_id);
+    match (pending, index.first_index_of((party, action.clone()))) {
+        (true, None) => index.push_back((party, action)),
+        (false, Some(position)) => {
+            index.remove(position);
         }
+        _ => return,
+    }
+    if index.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        save(env, &key, &index);
+    }
+}
 
-        let escrow = Escrow {
-            depositor: depositor.clone(),
-            recipient: recipient.clone(),
-            token_address: token_address.clone(),
+fn get_index_count_key(index: &Symbol, address: &Address) -> (Symbol, Symbol, Address) {
+    (symbol_short!("idx_n"), index.clone(), address.clone())
+}
+
+fn get_index_page_key(
+    index: &Symbol,
+    address: &Address,
+    page: u32,
+) -> (Symbol, Symbol, Address, u32) {
+    (
+        symbol_short!("idx_pg"),
+        index.clone(),
+        address.clone(),
+        page,
+    )
+}
+
+fn get_index_slot_key(
+    index: &Symbol,
+    address: &Address,
+    id: u64,
+) -> (Symbol, Symbol, Address, u64) {
+    (symbol_short!("idx_at"), index.clone(), address.clone(), id)
+}
+
+fn get_request_outbox_key(recipient: &Address) -> (Symbol, Address) {
+    (symbol_short!("req_out"), recipient.clone())
+}
+
+fn get_template_key(template_id: u64) -> (Symbol, u64) {
+    (symbol_short!("template"), template_id)
+}
+
+fn get_template_arbiter_key(template_id: u64) -> (Symbol, u64) {
+    (symbol_short!("tpl_arb"), template_id)
+}
+
+fn get_escrow_arbiter_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("arbiter"), escrow_id)
+}
+
+fn get_metadata_key(escrow_id: u64) -> (Symbol, u64) {
+    (symbol_short!("meta"), escrow_id)
+}
+
+fn get_external_ref_key(depositor: &Address, external_ref: &String) -> (Symbol, Address, String) {
+    (
+        symbol_short!("ext_ref"),
+        depositor.clone(),
+        external_ref.clone(),
+    )
+}
+
+fn validate_metadata(metadata: &EscrowMetadata) -> Result<(), Error> {
+    if metadata.external_ref.len() > MAX_EXTERNAL_REF_LEN
+        || metadata.tags.len() > MAX_TAGS
+        || metadata.entries.len() > MAX_METADATA_ENTRIES
+    {
+        return Err(Error::VectorTooLarge);
+    }
+    for value in metadata.entries.values().iter() {
+        if value.len() > MAX_METADATA_VALUE_LEN {
+            return Err(Error::VectorTooLarge);
+        }
+    }
+    Ok(())
+}
+
+fn store_metadata(env: &Env, escrow_id: u64, depositor: &Address, metadata: &EscrowMetadata) {
+    let key = get_metadata_key(escrow_id);
+    save(env, &key, metadata);
+    if metadata.external_ref.len() > 0 {
+        add_to_index(
+            env,
+            &get_external_ref_key(depositor, &metadata.external_ref),
+            escrow_id,
+        );
+    }
+}
+
+fn get_request_key(request_id: u64) -> (Symbol, u64) {
+    (symbol_short!("request"), request_id)
+}
+
+fn add_to_index<K: IntoVal<Env, Val>>(env: &Env, key: &K, id: u64) {
+    let mut ids: Vec<u64> = env.storage().persistent().get(key).unwrap_or(Vec::new(env));
+    if !ids.contains(id) {
+        ids.push_back(id);
+        save(env, key, &ids);
+    }
+}
+
+fn remove_from_index<K: IntoVal<Env, Val>>(env: &Env, key: &K, id: u64) {
+    let mut ids: Vec<u64> = env.storage().persistent().get(key).unwrap_or(Vec::new(env));
+    if let Some(position) = ids.first_index_of(id) {
+        ids.remove(position);
+        save(env, key, &ids);
+    }
+}
+
+/// Appends an id to one of the address's paged indexes unless it is listed.
+fn add_to_paged_index(env: &Env, index: Symbol, address: &Address, id: u64) {
+    let slot_key = get_index_slot_key(&index, address, id);
+    if env.storage().persistent().has(&slot_key) {
+        return;
+    }
+    let count_key = get_index_count_key(&index, address);
+    let slot: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+    add_to_index(
+        env,
+        &get_index_page_key(&index, address, slot / INDEX_PAGE),
+        id,
+    );
+    save(env, &slot_key, &slot);
+    save(env, &count_key, &(slot + 1));
+}
+
+/// Drops an id from the page of the address's paged index holding it.
+fn remove_from_paged_index(env: &Env, index: Symbol, address: &Address, id: u64) {
+    let slot_key = get_index_slot_key(&index, address, id);
+    let slot: Option<u32> = env.storage().persistent().get(&slot_key);
+    if let Some(slot) = slot {
+        remove_from_index(
+            env,
+            &get_index_page_key(&index, address, slot / INDEX_PAGE),
+            id,
+        );
+        env.storage().persistent().remove(&slot_key);
+    }
+}
+
+fn index_page(env: &Env, index: Symbol, address: &Address, page: u32) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&get_index_page_key(&index, address, page))
+        .unwrap_or(Vec::new(env))
+}
+
+fn index_pages(env: &Env, index: Symbol, address: &Address) -> u32 {
+    let slots: u32 = env
+        .storage()
+        .persistent()
+        .get(&get_index_count_key(&index, address))
+        .unwrap_or(0);
+    slots.div_ceil(INDEX_PAGE)
+}
+
+fn party_of(escrow: &Escrow, address: &Address) -> Option<Party> {
+    if *address == escrow.depositor {
+        Some(Party::Depositor)
+    } else if *address == escrow.recipient {
+        Some(Party::Recipient)
+    } else {
+        None
+    }
+}
+
+/// Whether `address` is the approver of any milestone.
+fn is_milestone_approver(
+    env: &Env,
+    escrow_id: u64,
+    escrow: &Escrow,
+    address: &Address,
+) -> Result<bool, Error> {
+    for index in 0..escrow.milestone_count {
+        match load_milestone(env, escrow_id, index)?.approval {
+            ApprovalRule::Depositor => {}
+            ApprovalRule::DepositorOrApprover(approver)
+            | ApprovalRule::DepositorAndApprover(approver) => {
+                if approver == *address {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// The party whose side a dispute raiser is on: parties stand for themselves
+/// and approvers, who sign off for the depositor, for the depositor.
+fn dispute_side(escrow: &Escrow, raiser: &Address) -> Address {
+    match party_of(escrow, raiser) {
+        Some(Party::Recipient) => escrow.recipient.clone(),
+        _ => escrow.depositor.clone(),
+    }
+}
+
+/// Enforces a milestone's approval rule on top of the depositor's own auth,
+/// or in place of it when `by_approver` is set.
+fn check_approval(milestone: &Milestone, by_approver: bool) -> Result<(), Error> {
+    match (&milestone.approval, by_approver) {
+        (ApprovalRule::DepositorAndApprover(approver), false) => {
+            approver.require_auth();
+            Ok(())
+        }
+        (ApprovalRule::DepositorOrApprover(_), true) => Ok(()),
+        (_, true) => Err(Error::UnauthorizedAccess),
+        (_, false) => Ok(()),
+    }
+}
+
+/// Authenticates a party for a gated action. Parties with a signer set
+/// must use `approve_action` instead.
+fn require_party_auth(
+    env: &Env,
+    escrow_id: u64,
+    party: Party,
+    address: &Address,
+) -> Result<(), Error> {
+    if env
+        .storage()
+        .persistent()
+        .has(&get_signers_key(escrow_id, party))
+    {
+        return Err(Error::MultisigRequired);
+    }
+    address.require_auth();
+    Ok(())
+}
+
+/// Writes a persistent entry and applies the TTL policy to it and the instance.
+fn save<K, V>(env: &Env, key: &K, value: &V)
+where
+    K: IntoVal<Env, Val>,
+    V: IntoVal<Env, Val>,
+{
+    env.storage().persistent().set(key, value);
+    env.storage()
+        .persistent()
+        .extend_ttl(key, ENTRY_TTL_THRESHOLD, ENTRY_TTL_EXTEND_TO);
+    bump_instance_ttl(env);
+}
+
+fn extend_if_present<K: IntoVal<Env, Val>>(env: &Env, key: &K) {
+    if env.storage().persistent().has(key) {
+        env.storage()
+            .persistent()
+            .extend_ttl(key, ENTRY_TTL_THRESHOLD, ENTRY_TTL_EXTEND_TO);
+    }
+}
+
+fn bump_instance_ttl(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(INSTANCE_TTL_THRESHOLD, INSTANCE_TTL_EXTEND_TO);
+}
+
+/// Fails if `operation` is paused globally or for `token`. In emergency mode
+/// only refunds pass, whatever the flags say; see `ensure_dispute_exit_open`.
+fn ensure_not_paused(env: &Env, operation: u32, token: &Address) -> Result<(), Error> {
+    if VaultixEscrow::is_emergency_mode(env.clone()) {
+        return if operation == PAUSE_REFUNDS {
+            Ok(())
+        } else {
+            Err(Error::ContractPaused)
+        };
+    }
+    let paused = VaultixEscrow::get_pause_flags(env.clone())
+        | VaultixEscrow::get_token_pause_flags(env.clone(), token.clone());
+    if paused & operation != 0 {
+        return Err(Error::ContractPaused);
+    }
+    Ok(())
+}
+
+/// Like `ensure_not_paused` for `PAUSE_DISPUTES`, for the calls that only wind
+/// a dispute down and so stay open in emergency mode.
+fn ensure_dispute_exit_open(env: &Env, token: &Address) -> Result<(), Error> {
+    if VaultixEscrow::is_emergency_mode(env.clone()) {
+        return Ok(());
+    }
+    ensure_not_paused(env, PAUSE_DISPUTES, token)
+}
+
+fn require_treasury(env: &Env) -> Result<Address, Error> {
+    let treasury: Address = env
+        .storage()
+        .instance()
+        .get(&symbol_short!("treasury"))
+        .ok_or(Error::TreasuryNotInitialized)?;
+    treasury.require_auth();
+    Ok(treasury)
+}
+
+fn get_token_pause_key(token: &Address) -> (Symbol, Address) {
+    (symbol_short!("tok_pause"), token.clone())
+}
+
+fn admin_storage_key() -> Symbol {
+    symbol_short!("admin")
+}
+
+fn get_admin(env: &Env) -> Result<Address, Error> {
+    env.storage()
+        .persistent()
+        .get(&admin_storage_key())
+        .ok_or(Error::AdminNotInitialized)
+}
+
+fn validate_milestones(
+    env: &Env,
+    milestones: &Vec<Milestone>,
+    deadline: u64,
+) -> Result<i128, Error> {
+    if milestones.len() > get_milestone_limit(env) {
+        return Err(Error::VectorTooLarge);
+    }
+    let mut total: i128 = 0;
+    for milestone in milestones.iter() {
+        if milestone.amount <= 0 {
+            return Err(Error::ZeroAmount);
+        }
+        if milestone.due_date > deadline {
+            return Err(Error::InvalidDeadline);
+        }
+        match milestone.kind {
+            MilestoneKind::Fixed => {}
+            MilestoneKind::Vesting(start, end) => {
+                if end <= start || end > deadline {
+                    return Err(Error::InvalidDeadline);
+                }
+            }
+            MilestoneKind::Scheduled(unlock_at) => {
+                if unlock_at > deadline {
+                    return Err(Error::InvalidDeadline);
+                }
+            }
+        }
+        if let ReleaseCondition::HashLock(_, timelock) = milestone.condition {
+            if timelock > deadline {
+                return Err(Error::InvalidDeadline);
+            }
+        }
+        if milestone.payees.len() > MAX_PAYEES
+            || milestone.metadata.title.len() > MAX_TITLE_LEN
+            || milestone.metadata.uri.len() > MAX_URI_LEN
+            || milestone.metadata.criteria.len() > MAX_CRITERIA
+        {
+            return Err(Error::VectorTooLarge);
+        }
+        if !milestone.payees.is_empty() {
+            let mut split: i128 = 0;
+            for payee in milestone.payees.iter() {
+                if payee.bps == 0 {
+                    return Err(Error::InvalidMilestoneAmount);
+                }
+                split += payee.bps as i128;
+            }
+            if split != BPS_DENOMINATOR {
+                return Err(Error::InvalidMilestoneAmount);
+            }
+        }
+        total = total
+            .checked_add(milestone.amount)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+    }
+    Ok(total)
+}
+
+/// Pays `amount` of `milestone` to its payees less the platform fee, which goes
+/// to the treasury. Returns `(payout, fee)`.
+fn pay_recipient(
+    env: &Env,
+    escrow: &Escrow,
+    milestone: &Milestone,
+    amount: i128,
+) -> Result<(i128, i128), Error> {
+    let (treasury, fee_bps) = VaultixEscrow::get_config(env.clone())?;
+    let fee = calculate_fee(amount, fee_bps)?;
+    let payout = amount
+        .checked_sub(fee)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+
+    let token_client = token::Client::new(env, &escrow.token_address);
+    pay_payees(env, &token_client, escrow, milestone, payout)?;
+
+    if fee > 0 {
+        token_client.transfer(&env.current_contract_address(), &treasury, &fee);
+    }
+
+    Ok((payout, fee))
+}
+
+/// Transfers `amount` to the milestone's payees by their bps split, or to the
+/// escrow recipient when it has none. The last payee receives any rounding
+/// remainder so the split always adds up to `amount`.
+fn pay_payees(
+    env: &Env,
+    token_client: &token::Client,
+    escrow: &Escrow,
+    milestone: &Milestone,
+    amount: i128,
+) -> Result<(), Error> {
+    if amount <= 0 {
+        return Ok(());
+    }
+    if milestone.payees.is_empty() {
+        token_client.transfer(&env.current_contract_address(), &escrow.recipient, &amount);
+        return Ok(());
+    }
+
+    let last = milestone.payees.len() - 1;
+    let mut paid: i128 = 0;
+    for (i, payee) in milestone.payees.iter().enumerate() {
+        let share = if i as u32 == last {
+            amount - paid
+        } else {
+            amount
+                .checked_mul(payee.bps as i128)
+                .ok_or(Error::InvalidMilestoneAmount)?
+                / BPS_DENOMINATOR
+        };
+        paid += share;
+        if share > 0 {
+            token_client.transfer(&env.current_contract_address(), &payee.address, &share);
+        }
+    }
+    Ok(())
+}
+
+/// Amount of a milestone not yet paid to the recipient.
+fn unpaid_amount(milestone: &Milestone) -> Result<i128, Error> {
+    milestone
+        .amount
+        .checked_sub(milestone.claimed)
+        .ok_or(Error::InvalidMilestoneAmount)
+}
+
+/// Portion of a milestone the recipient has earned at `now`.
+fn vested_amount(milestone: &Milestone, now: u64) -> Result<i128, Error> {
+    match milestone.kind {
+        MilestoneKind::Fixed => Ok(0),
+        MilestoneKind::Scheduled(unlock_at) => {
+            if now >= unlock_at {
+                Ok(milestone.amount)
+            } else {
+                Ok(0)
+            }
+        }
+        MilestoneKind::Vesting(start, end) => {
+            if now <= start {
+                return Ok(0);
+            }
+            if now >= end {
+                return Ok(milestone.amount);
+            }
+            milestone
+                .amount
+                .checked_mul((now - start) as i128)
+                .and_then(|v| v.checked_div((end - start) as i128))
+                .ok_or(Error::InvalidMilestoneAmount)
+        }
+    }
+}
+
+/// Shifts a vesting window or unlock time so time spent in dispute does not accrue.
+fn freeze_vesting(kind: MilestoneKind, frozen_at: u64, now: u64) -> MilestoneKind {
+    match kind {
+        MilestoneKind::Vesting(start, end) if frozen_at < end && now > frozen_at.max(start) => {
+            let paused = now - frozen_at.max(start);
+            MilestoneKind::Vesting(start.saturating_add(paused), end.saturating_add(paused))
+        }
+        MilestoneKind::Scheduled(unlock_at) if frozen_at < unlock_at && now > frozen_at => {
+            MilestoneKind::Scheduled(unlock_at.saturating_add(now - frozen_at))
+        }
+        other => other,
+    }
+}
+
+/// Expands a payment schedule into one milestone per period. Each period unlocks
+/// at the end of its interval; periods falling inside the cliff unlock together
+/// when it ends.
+fn build_schedule(env: &Env, schedule: &PaymentSchedule) -> Result<Vec<Milestone>, Error> {
+    if schedule.periods == 0 || schedule.interval == 0 {
+        return Err(Error::InvalidSchedule);
+    }
+
+    let cliff_end = schedule
+        .start
+        .checked_add(schedule.cliff)
+        .ok_or(Error::InvalidDeadline)?;
+    let mut milestones = Vec::new(env);
+    for period in 1..=schedule.periods {
+        let unlock_at = schedule
+            .interval
+            .checked_mul(period as u64)
+            .and_then(|offset| schedule.start.checked_add(offset))
+            .ok_or(Error::InvalidDeadline)?;
+        milestones.push_back(Milestone {
+            amount: schedule.period_amount,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("period"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Scheduled(unlock_at.max(cliff_end)),
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: Vec::new(env),
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(env),
+        });
+    }
+    Ok(milestones)
+}
+
+fn open_escrow(
+    env: &Env,
+    escrow_id: u64,
+    depositor: Address,
+    recipient: Address,
+    token_address: Address,
+    milestones: Vec<Milestone>,
+    deadline: u64,
+) -> Result<(), Error> {
+    if depositor == recipient {
+        return Err(Error::SelfDealing);
+    }
+
+    let storage_key = get_storage_key(escrow_id);
+    if env.storage().persistent().has(&storage_key)
+        || env.storage().persistent().has(&get_receipt_key(escrow_id))
+    {
+        return Err(Error::EscrowAlreadyExists);
+    }
+
+    let total_amount = validate_milestones(env, &milestones, deadline)?;
+
+    let mut metadata = Vec::new(env);
+    let mut hashlock_until = 0;
+    for (index, milestone) in milestones.iter().enumerate() {
+        if let ReleaseCondition::HashLock(_, timelock) = milestone.condition {
+            hashlock_until = hashlock_until.max(timelock);
+        }
+        let mut m = milestone.clone();
+        m.status = MilestoneStatus::Pending;
+        m.submitted_at = 0;
+        m.claimed = 0;
+        store_milestone(env, escrow_id, index as u32, &m);
+        metadata.push_back(m.metadata);
+    }
+
+    let escrow = Escrow {
+        depositor: depositor.clone(),
+        recipient: recipient.clone(),
+        token_address: token_address.clone(),
+        total_amount,
+        total_released: 0,
+        total_refunded: 0,
+        milestone_count: milestones.len(),
+        open_milestones: milestones.len(),
+        status: EscrowStatus::Created,
+        deadline,
+        resolution: Resolution::None,
+        assignment_consent: false,
+        template_id: 0,
+        hashlock_until,
+    };
+
+    save(env, &storage_key, &escrow);
+    add_to_paged_index(env, symbol_short!("parties"), &depositor, escrow_id);
+    add_to_paged_index(env, symbol_short!("parties"), &recipient, escrow_id);
+
+    // Standardized Event
+    publish_escrow_event(
+        env,
+        escrow_id,
+        EscrowCreatedEvent {
+            depositor,
+            recipient,
+            token_address,
             total_amount,
-            total_released: 0,
-            milestones: initialized_milestones,
-            status: EscrowStatus::Created,
             deadline,
-            resolution: Resolution::None,
-        };
+            milestones: metadata,
+        },
+    );
+
+    Ok(())
+}
+
+/// Pulls the escrow total from the depositor and marks the escrow Active.
+fn fund_escrow(env: &Env, escrow_id: u64, mut escrow: Escrow) -> Result<(), Error> {
+    let token_client = token::Client::new(env, &escrow.token_address);
+    token_client.transfer_from(
+        &env.current_contract_address(),
+        &escrow.depositor,
+        &env.current_contract_address(),
+        &escrow.total_amount,
+    );
+
+    let storage_key = get_storage_key(escrow_id);
+    escrow.status = EscrowStatus::Active;
+    save(env, &storage_key, &escrow);
+
+    // Standardized Event
+    publish_escrow_event(
+        env,
+        escrow_id,
+        EscrowFundedEvent {
+            amount: escrow.total_amount,
+        },
+    );
+
+    Ok(())
+}
 
-        env.storage().persistent().set(&storage_key, &escrow);
-        env.storage()
-            .persistent()
-            .extend_ttl(&storage_key, 100, 2_000_000);
+/// Makes room for one more request sent by `recipient`, closing its lapsed
+/// requests first.
+fn ensure_request_capacity(env: &Env, recipient: &Address) -> Result<(), Error> {
+    let ids: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&get_request_outbox_key(recipient))
+        .unwrap_or(Vec::new(env));
+    if ids.len() < MAX_OPEN_REQUESTS {
+        return Ok(());
+    }
+    let now = env.ledger().timestamp();
+    let mut open = ids.len();
+    for request_id in ids.iter() {
+        let request = VaultixEscrow::get_escrow_request(env.clone(), request_id)?;
+        if request.expires_at <= now {
+            close_request(env, request_id, &request.depositor, &request.recipient);
+            open -= 1;
+        }
+    }
+    if open >= MAX_OPEN_REQUESTS {
+        return Err(Error::VectorTooLarge);
+    }
+    Ok(())
+}
 
-        // Standardized Event
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "EscrowCreated"),
-                escrow_id,
-            ),
-            (depositor, recipient, token_address, total_amount, deadline),
-        );
+fn close_request(env: &Env, request_id: u64, depositor: &Address, recipient: &Address) {
+    env.storage()
+        .persistent()
+        .remove(&get_request_key(request_id));
+    remove_from_index(env, &get_request_outbox_key(recipient), request_id);
+    remove_from_paged_index(env, symbol_short!("requests"), depositor, request_id);
+    remove_from_paged_index(env, symbol_short!("requests"), recipient, request_id);
+}
 
-        Ok(())
+/// Balance still held by the contract for a disputed escrow.
+/// Disputes raised before funding have nothing to distribute.
+fn disputed_balance(escrow: &Escrow, dispute: &Dispute) -> Result<i128, Error> {
+    if dispute.previous_status == EscrowStatus::Created {
+        return Ok(0);
     }
+    outstanding_balance(escrow)
+}
 
-    pub fn deposit_funds(env: Env, escrow_id: u64) -> Result<(), Error> {
-        let storage_key = get_storage_key(escrow_id);
-        ensure_not_paused(&env)?;
+/// Funds not yet paid to the recipient or refunded to the depositor.
+fn outstanding_balance(escrow: &Escrow) -> Result<i128, Error> {
+    escrow
+        .total_amount
+        .checked_sub(escrow.total_released)
+        .and_then(|v| v.checked_sub(escrow.total_refunded))
+        .ok_or(Error::InvalidMilestoneAmount)
+}
 
-        let mut escrow: Escrow = env
-            .storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)?;
-        escrow.depositor.require_auth();
+/// Applies a ruling straight away, or records it for appeal when an appeal
+/// window is configured.
+fn issue_ruling(
+    env: &Env,
+    escrow_id: u64,
+    mut escrow: Escrow,
+    mut dispute: Dispute,
+    winner: Address,
+    arbiters: Vec<Address>,
+) -> Result<(), Error> {
+    let window = match VaultixEscrow::get_appeal_config(env.clone()) {
+        Some(config) if config.window > 0 => config.window,
+        _ => return apply_ruling(env, escrow_id, escrow, &dispute, winner, &arbiters),
+    };
+
+    let ruled_at = env.ledger().timestamp();
+    dispute.ruling = if winner == escrow.recipient {
+        Resolution::Recipient
+    } else {
+        Resolution::Depositor
+    };
+    dispute.ruling_arbiters = arbiters;
+    dispute.ruled_at = ruled_at;
+    dispute.appeal_deadline = ruled_at.saturating_add(window);
+    save(env, &get_dispute_key(escrow_id), &dispute);
+
+    escrow.status = EscrowStatus::RulingPending;
+    save(env, &get_storage_key(escrow_id), &escrow);
+
+    publish_escrow_event(
+        env,
+        escrow_id,
+        RulingRecordedEvent {
+            winner,
+            appeal_deadline: dispute.appeal_deadline,
+        },
+    );
 
-        if escrow.status != EscrowStatus::Created {
-            return Err(Error::EscrowAlreadyFunded);
+    Ok(())
+}
+
+/// Pays out a disputed escrow according to `winner` and closes it as Resolved.
+/// The arbiter fee is split evenly between `arbiters`, with any rounding
+/// remainder going to the first of them.
+fn apply_ruling(
+    env: &Env,
+    escrow_id: u64,
+    mut escrow: Escrow,
+    dispute: &Dispute,
+    winner: Address,
+    arbiters: &Vec<Address>,
+) -> Result<(), Error> {
+    let outstanding = disputed_balance(&escrow, dispute)?;
+    let arbiter_fee = calculate_fee(outstanding, get_arbiter_fee_bps(env))?;
+    let payout = outstanding
+        .checked_sub(arbiter_fee)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+    let token_client = token::Client::new(env, &escrow.token_address);
+
+    if arbiter_fee > 0 {
+        let share = arbiter_fee / arbiters.len() as i128;
+        let remainder = arbiter_fee - share * arbiters.len() as i128;
+        for (i, arbiter) in arbiters.iter().enumerate() {
+            let amount = if i == 0 { share + remainder } else { share };
+            if amount > 0 {
+                token_client.transfer(&env.current_contract_address(), &arbiter, &amount);
+            }
         }
+    }
 
-        let token_client = token::Client::new(&env, &escrow.token_address);
-        token_client.transfer_from(
+    let close_out = if winner == escrow.recipient {
+        escrow.total_released = escrow
+            .total_released
+            .checked_add(outstanding)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        escrow.resolution = Resolution::Recipient;
+        SweepAction::Payout(MilestoneStatus::Released, payout, outstanding)
+    } else {
+        escrow.total_refunded = escrow
+            .total_refunded
+            .checked_add(outstanding)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        escrow.resolution = Resolution::Depositor;
+
+        if payout > 0 {
+            token_client.transfer(&env.current_contract_address(), &escrow.depositor, &payout);
+        }
+        SweepAction::Payout(MilestoneStatus::Refunded, 0, outstanding)
+    };
+
+    // Bonds go back to a raiser or appellant whose side wins, otherwise to the
+    // party the ruling favours. Only parties appeal, so the appeal bond always
+    // follows the winner; an approver who raised the dispute is judged
+    // by the side they sit on.
+    let bond_recipient = if dispute_side(&escrow, &dispute.raised_by) == winner {
+        dispute.raised_by.clone()
+    } else {
+        winner.clone()
+    };
+    if dispute.bond > 0 {
+        token_client.transfer(
             &env.current_contract_address(),
-            &escrow.depositor,
+            &bond_recipient,
+            &dispute.bond,
+        );
+    }
+    if dispute.appeal_bond > 0 {
+        token_client.transfer(
             &env.current_contract_address(),
-            &escrow.total_amount,
+            &winner,
+            &dispute.appeal_bond,
         );
+    }
 
-        escrow.status = EscrowStatus::Active;
-        env.storage().persistent().set(&storage_key, &escrow);
-        env.storage()
-            .persistent()
-            .extend_ttl(&storage_key, 100, 2_000_000);
+    escrow.status = EscrowStatus::Resolved;
+    env.storage()
+        .persistent()
+        .remove(&get_settlement_key(escrow_id));
 
-        // Standardized Event
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "EscrowFunded"),
-                escrow_id,
-            ),
-            escrow.total_amount,
-        );
+    // Standardized Event
+    publish_escrow_event(
+        env,
+        escrow_id,
+        DisputeResolvedEvent {
+            winner,
+            payout,
+            arbiter_fee,
+            bond: dispute.bond,
+            bond_recipient,
+        },
+    );
 
-        Ok(())
-    }
+    start_sweep(env, escrow_id, &mut escrow, close_out)?;
+    save(env, &get_storage_key(escrow_id), &escrow);
 
-    pub fn get_escrow(env: Env, escrow_id: u64) -> Result<Escrow, Error> {
-        let storage_key = get_storage_key(escrow_id);
-        env.storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)
-    }
+    Ok(())
+}
 
-    pub fn get_state(env: Env, escrow_id: u64) -> Result<EscrowStatus, Error> {
-        let escrow = Self::get_escrow(env, escrow_id)?;
-        Ok(escrow.status)
+fn ruling_winner(escrow: &Escrow, ruling: Resolution) -> Result<Address, Error> {
+    match ruling {
+        Resolution::Depositor => Ok(escrow.depositor.clone()),
+        Resolution::Recipient => Ok(escrow.recipient.clone()),
+        _ => Err(Error::InvalidEscrowStatus),
     }
+}
 
-    pub fn release_milestone(env: Env, escrow_id: u64, milestone_index: u32) -> Result<(), Error> {
-        let storage_key = get_storage_key(escrow_id);
-        ensure_not_paused(&env)?;
-
-        let mut escrow: Escrow = env
-            .storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)?;
-        escrow.depositor.require_auth();
+fn counterparty(escrow: &Escrow, party: &Address) -> Address {
+    if *party == escrow.depositor {
+        escrow.recipient.clone()
+    } else {
+        escrow.depositor.clone()
+    }
+}
 
-        if escrow.status != EscrowStatus::Active {
-            return Err(Error::EscrowNotActive);
-        }
-        if milestone_index >= escrow.milestones.len() {
-            return Err(Error::MilestoneNotFound);
-        }
+/// Starts work that touches every milestone and runs its first page.
+/// The caller saves the header.
+fn start_sweep(
+    env: &Env,
+    escrow_id: u64,
+    escrow: &mut Escrow,
+    action: SweepAction,
+) -> Result<(), Error> {
+    let sweep = MilestoneSweep {
+        action,
+        next: 0,
+        end: escrow.milestone_count,
+        refunded: 0,
+    };
+    sweep_page(env, escrow_id, escrow, sweep)?;
+    Ok(())
+}
 
-        let mut milestone = escrow
-            .milestones
-            .get(milestone_index)
-            .ok_or(Error::MilestoneNotFound)?;
-        if milestone.status == MilestoneStatus::Released {
-            return Err(Error::MilestoneAlreadyReleased);
+/// Runs one page of a sweep and returns how many milestones are left,
+/// finishing the operation after the last one. The caller saves the header.
+fn sweep_page(
+    env: &Env,
+    escrow_id: u64,
+    escrow: &mut Escrow,
+    mut sweep: MilestoneSweep,
+) -> Result<u32, Error> {
+    let token_client = token::Client::new(env, &escrow.token_address);
+    let stop = sweep.next.saturating_add(SWEEP_PAGE).min(sweep.end);
+    let mut refund: i128 = 0;
+    for index in sweep.next..stop {
+        let mut milestone = load_milestone(env, escrow_id, index)?;
+        let changed = match &mut sweep.action {
+            SweepAction::Resume(raised_at, withdrawn_at, _) => resume_milestone(
+                env,
+                escrow_id,
+                index,
+                &mut milestone,
+                *raised_at,
+                *withdrawn_at,
+            ),
+            _ if is_terminal_milestone(milestone.status) => false,
+            SweepAction::Payout(status, payout, outstanding) => {
+                let unpaid = unpaid_amount(&milestone)?;
+                let share = if *payout <= 0 || unpaid <= 0 {
+                    0
+                } else if unpaid >= *outstanding {
+                    *payout
+                } else {
+                    payout
+                        .checked_mul(unpaid)
+                        .ok_or(Error::InvalidMilestoneAmount)?
+                        / *outstanding
+                };
+                pay_payees(env, &token_client, escrow, &milestone, share)?;
+                *payout -= share;
+                *outstanding -= unpaid;
+                close_milestone(escrow, &mut milestone, *status)?;
+                true
+            }
+            SweepAction::Refund(status, closed_at) => {
+                // What vested or unlocked before closing was earned
+                let accrued = vested_amount(&milestone, *closed_at)?
+                    .checked_sub(milestone.claimed)
+                    .ok_or(Error::InvalidMilestoneAmount)?;
+                if accrued > 0 {
+                    let (payout, fee) = pay_recipient(env, escrow, &milestone, accrued)?;
+                    milestone.claimed = milestone
+                        .claimed
+                        .checked_add(accrued)
+                        .ok_or(Error::InvalidMilestoneAmount)?;
+                    escrow.total_released = escrow
+                        .total_released
+                        .checked_add(accrued)
+                        .ok_or(Error::InvalidMilestoneAmount)?;
+                    publish_escrow_event(
+                        env,
+                        escrow_id,
+                        VestedClaimedEvent {
+                            milestone_index: index,
+                            payout,
+                            fee,
+                            claimed: milestone.claimed,
+                        },
+                    );
+                }
+                let unpaid = unpaid_amount(&milestone)?;
+                refund = refund
+                    .checked_add(unpaid)
+                    .ok_or(Error::InvalidMilestoneAmount)?;
+                let status = if unpaid == 0 {
+                    MilestoneStatus::Released
+                } else {
+                    *status
+                };
+                close_milestone(escrow, &mut milestone, status)?;
+                true
+            }
+            SweepAction::Purge => return Err(Error::InvalidEscrowStatus),
+        };
+        if changed {
+            store_milestone(env, escrow_id, index, &milestone);
         }
+    }
 
-        let (treasury, fee_bps) = Self::get_config(env.clone())?;
-        let fee = calculate_fee(milestone.amount, fee_bps)?;
-        let payout = milestone
-            .amount
+    if refund > 0 {
+        escrow.total_refunded = escrow
+            .total_refunded
+            .checked_add(refund)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+        let fee = match VaultixEscrow::get_config(env.clone()) {
+            Ok((treasury, fee_bps)) => {
+                let fee = calculate_fee(refund, fee_bps)?;
+                if fee > 0 {
+                    token_client.transfer(&env.current_contract_address(), &treasury, &fee);
+                }
+                fee
+            }
+            Err(_) => 0,
+        };
+        let refund_amount = refund
             .checked_sub(fee)
             .ok_or(Error::InvalidMilestoneAmount)?;
+        if refund_amount > 0 {
+            token_client.transfer(
+                &env.current_contract_address(),
+                &escrow.depositor,
+                &refund_amount,
+            );
+        }
+        sweep.refunded = sweep
+            .refunded
+            .checked_add(refund_amount)
+            .ok_or(Error::InvalidMilestoneAmount)?;
+    }
 
-        let token_client = token::Client::new(&env, &escrow.token_address);
-        token_client.transfer(&env.current_contract_address(), &escrow.recipient, &payout);
-
-        if fee > 0 {
-            token_client.transfer(&env.current_contract_address(), &treasury, &fee);
+    let left = advance_sweep(env, escrow_id, &mut sweep, stop);
+    if left > 0 {
+        return Ok(left);
+    }
+    match sweep.action {
+        SweepAction::Resume(_, _, previous_status) => escrow.status = previous_status,
+        SweepAction::Payout(_, payout, _) => {
+            // No open milestone was left to carry the remainder
+            if payout > 0 {
+                token_client.transfer(&env.current_contract_address(), &escrow.recipient, &payout);
+            }
+            ensure_milestones_terminal(escrow)?;
+        }
+        SweepAction::Refund(_, closed_at) => {
+            ensure_milestones_terminal(escrow)?;
+            if escrow.status == EscrowStatus::Expired {
+                publish_escrow_event(
+                    env,
+                    escrow_id,
+                    RefundExpiredEvent {
+                        depositor: escrow.depositor.clone(),
+                        refund_amount: sweep.refunded,
+                        refunded_at: closed_at,
+                    },
+                );
+            }
         }
+        SweepAction::Purge => {}
+    }
+    Ok(0)
+}
 
-        milestone.status = MilestoneStatus::Released;
-        escrow.milestones.set(milestone_index, milestone.clone());
+/// Removes a page of an archived escrow's milestones and their delivery
+/// proofs, returning how many are left.
+fn purge_page(env: &Env, escrow_id: u64, mut sweep: MilestoneSweep) -> u32 {
+    let storage = env.storage().persistent();
+    let stop = sweep.next.saturating_add(SWEEP_PAGE).min(sweep.end);
+    for index in sweep.next..stop {
+        let key = get_milestone_key(escrow_id, index);
+        let milestone: Option<Milestone> = storage.get(&key);
+        // A proof is stored exactly while the milestone carries a submission
+        if milestone.is_some_and(|m| m.submitted_at != 0) {
+            storage.remove(&get_proof_key(escrow_id, index));
+        }
+        storage.remove(&key);
+    }
+    advance_sweep(env, escrow_id, &mut sweep, stop)
+}
 
-        escrow.total_released = escrow
-            .total_released
-            .checked_add(milestone.amount)
-            .ok_or(Error::InvalidMilestoneAmount)?;
+/// Moves a sweep past the page ending at `stop`, keeping it for the next
+/// call while milestones are left. Returns how many are left.
+fn advance_sweep(env: &Env, escrow_id: u64, sweep: &mut MilestoneSweep, stop: u32) -> u32 {
+    let key = get_sweep_key(escrow_id);
+    sweep.next = stop;
+    let left = sweep.end - stop;
+    if left > 0 {
+        save(env, &key, sweep);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+    left
+}
 
-        env.storage().persistent().set(&storage_key, &escrow);
+/// Restores a milestone after a withdrawn dispute. Returns whether it changed.
+fn resume_milestone(
+    env: &Env,
+    escrow_id: u64,
+    index: u32,
+    milestone: &mut Milestone,
+    raised_at: u64,
+    withdrawn_at: u64,
+) -> bool {
+    let kind = freeze_vesting(milestone.kind, raised_at, withdrawn_at);
+    // `Disputed` is only found on milestones frozen by earlier contract versions
+    let delivered = matches!(
+        milestone.status,
+        MilestoneStatus::Submitted | MilestoneStatus::Disputed
+    );
+    if kind == milestone.kind && !delivered {
+        return false;
+    }
+    milestone.kind = kind;
+    if delivered {
+        // Deliveries must be resubmitted so the review period restarts
+        milestone.status = MilestoneStatus::Pending;
+        milestone.submitted_at = 0;
         env.storage()
             .persistent()
-            .extend_ttl(&storage_key, 100, 2_000_000);
+            .remove(&get_proof_key(escrow_id, index));
+    }
+    true
+}
 
-        // Standardized Event
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "MilestoneReleased"),
-                escrow_id,
-                milestone_index,
-            ),
-            (payout, fee),
-        );
+/// Closes an open milestone with `status`, counting it off the header.
+fn close_milestone(
+    escrow: &mut Escrow,
+    milestone: &mut Milestone,
+    status: MilestoneStatus,
+) -> Result<(), Error> {
+    milestone.status = status;
+    // Count down from the header so a drifted counter still trips
+    // `ensure_milestones_terminal` instead of being papered over.
+    escrow.open_milestones = escrow
+        .open_milestones
+        .checked_sub(1)
+        .ok_or(Error::MilestoneStateInconsistent)?;
+    Ok(())
+}
 
-        Ok(())
-    }
+fn get_milestone_key(escrow_id: u64, index: u32) -> (Symbol, u64, u32) {
+    (symbol_short!("milestone"), escrow_id, index)
+}
 
-    pub fn confirm_delivery(
-        env: Env,
-        escrow_id: u64,
-        milestone_index: u32,
-        buyer: Address,
-    ) -> Result<(), Error> {
-        let storage_key = get_storage_key(escrow_id);
-        ensure_not_paused(&env)?;
+fn load_milestone(env: &Env, escrow_id: u64, index: u32) -> Result<Milestone, Error> {
+    env.storage()
+        .persistent()
+        .get(&get_milestone_key(escrow_id, index))
+        .ok_or(Error::MilestoneNotFound)
+}
 
-        let mut escrow: Escrow = env
-            .storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)?;
-        buyer.require_auth();
+fn store_milestone(env: &Env, escrow_id: u64, index: u32, milestone: &Milestone) {
+    let key = get_milestone_key(escrow_id, index);
+    save(env, &key, milestone);
+}
 
-        if escrow.depositor != buyer {
-            return Err(Error::UnauthorizedAccess);
-        }
-        if escrow.status != EscrowStatus::Active {
-            return Err(Error::EscrowNotActive);
+/// Writes back one milestone and keeps the header's open count in step.
+/// The caller still saves the header.
+fn put_milestone(
+    env: &Env,
+    escrow_id: u64,
+    escrow: &mut Escrow,
+    index: u32,
+    milestone: &Milestone,
+) -> Result<(), Error> {
+    let before = load_milestone(env, escrow_id, index)?;
+    match (
+        is_terminal_milestone(before.status),
+        is_terminal_milestone(milestone.status),
+    ) {
+        (false, true) => {
+            escrow.open_milestones = escrow
+                .open_milestones
+                .checked_sub(1)
+                .ok_or(Error::MilestoneStateInconsistent)?;
         }
-        if milestone_index >= escrow.milestones.len() {
-            return Err(Error::MilestoneNotFound);
+        (true, false) => {
+            escrow.open_milestones = escrow
+                .open_milestones
+                .checked_add(1)
+                .ok_or(Error::MilestoneStateInconsistent)?;
         }
+        _ => {}
+    }
+    store_milestone(env, escrow_id, index, milestone);
+    Ok(())
+}
 
-        let mut milestone = escrow
-            .milestones
-            .get(milestone_index)
-            .ok_or(Error::MilestoneNotFound)?;
-        if milestone.status == MilestoneStatus::Released {
-            return Err(Error::MilestoneAlreadyReleased);
-        }
+/// Rejects milestones that were already paid, refunded or cancelled.
+fn ensure_milestone_open(milestone: &Milestone) -> Result<(), Error> {
+    match milestone.status {
+        MilestoneStatus::Released => Err(Error::MilestoneAlreadyReleased),
+        status if is_terminal_milestone(status) => Err(Error::InvalidMilestoneStatus),
+        _ => Ok(()),
+    }
+}
 
-        milestone.status = MilestoneStatus::Released;
-        escrow.milestones.set(milestone_index, milestone.clone());
+fn is_terminal_milestone(status: MilestoneStatus) -> bool {
+    matches!(
+        status,
+        MilestoneStatus::Released | MilestoneStatus::Refunded | MilestoneStatus::Cancelled
+    )
+}
 
-        escrow.total_released = escrow
-            .total_released
-            .checked_add(milestone.amount)
-            .ok_or(Error::InvalidMilestoneAmount)?;
+/// Invariant: an escrow in a final state has no milestone left open.
+fn ensure_milestones_terminal(escrow: &Escrow) -> Result<(), Error> {
+    let escrow_terminal = matches!(
+        escrow.status,
+        EscrowStatus::Completed
+            | EscrowStatus::Cancelled
+            | EscrowStatus::Resolved
+            | EscrowStatus::Expired
+    );
+    if escrow_terminal && escrow.open_milestones > 0 {
+        return Err(Error::MilestoneStateInconsistent);
+    }
+    Ok(())
+}
 
-        let token_client = token::Client::new(&env, &escrow.token_address);
-        token_client.transfer(
-            &env.current_contract_address(),
-            &escrow.recipient,
-            &milestone.amount,
-        );
+/// Calculate platform fee using basis points (BPS)
+/// Formula: fee = (amount * fee_bps) / 10000
+/// Uses checked arithmetic to prevent overflow
+fn calculate_fee(amount: i128, fee_bps: i128) -> Result<i128, Error> {
+    // Multiply amount by fee basis points with overflow protection
+    let fee_numerator = amount
+        .checked_mul(fee_bps)
+        .ok_or(Error::InvalidMilestoneAmount)?;
 
-        env.storage().persistent().set(&storage_key, &escrow);
+    // Divide by BPS denominator (10000) to get final fee
+    let fee = fee_numerator
+        .checked_div(BPS_DENOMINATOR)
+        .ok_or(Error::InvalidMilestoneAmount)?;
 
-        // Standardized Event
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "MilestoneReleased"),
-                escrow_id,
-                milestone_index,
-            ),
-            (milestone.amount, 0i128),
-        );
+    Ok(fee)
+}
 
-        Ok(())
+fn execute_release_milestone(
+    env: Env,
+    escrow_id: u64,
+    milestone_index: u32,
+    by_approver: bool,
+) -> Result<(), Error> {
+    let storage_key = get_storage_key(escrow_id);
+    let mut escrow: Escrow = env
+        .storage()
+        .persistent()
+        .get(&storage_key)
+        .ok_or(Error::EscrowNotFound)?;
+    ensure_not_paused(&env, PAUSE_RELEASES, &escrow.token_address)?;
+
+    if escrow.status != EscrowStatus::Active {
+        return Err(Error::EscrowNotActive);
+    }
+    if milestone_index >= escrow.milestone_count {
+        return Err(Error::MilestoneNotFound);
     }
 
-    pub fn raise_dispute(env: Env, escrow_id: u64, caller: Address) -> Result<(), Error> {
-        let storage_key = get_storage_key(escrow_id);
-        ensure_not_paused(&env)?;
+    let mut milestone = load_milestone(&env, escrow_id, milestone_index)?;
+    ensure_milestone_open(&milestone)?;
+    check_approval(&milestone, by_approver)?;
 
-        let mut escrow: Escrow = env
-            .storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)?;
+    let remaining = unpaid_amount(&milestone)?;
+    let (payout, fee) = pay_recipient(&env, &escrow, &milestone, remaining)?;
 
-        if caller != escrow.depositor && caller != escrow.recipient {
-            return Err(Error::UnauthorizedAccess);
-        }
-        caller.require_auth();
+    milestone.status = MilestoneStatus::Released;
+    milestone.claimed = milestone.amount;
+    put_milestone(&env, escrow_id, &mut escrow, milestone_index, &milestone)?;
 
-        if escrow.status == EscrowStatus::Disputed {
-            return Err(Error::AlreadyInDispute);
-        }
-        if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::Created {
-            return Err(Error::InvalidEscrowStatus);
-        }
+    escrow.total_released = escrow
+        .total_released
+        .checked_add(remaining)
+        .ok_or(Error::InvalidMilestoneAmount)?;
 
-        let mut updated_milestones = Vec::new(&env);
-        for milestone in escrow.milestones.iter() {
-            let mut m = milestone.clone();
-            if m.status == MilestoneStatus::Pending {
-                m.status = MilestoneStatus::Disputed;
-            }
-            updated_milestones.push_back(m);
+    save(&env, &storage_key, &escrow);
+
+    // Standardized Event
+    publish_escrow_event(
+        &env,
+        escrow_id,
+        MilestoneReleasedEvent {
+            milestone_index,
+            payout,
+            fee,
+        },
+    );
+
+    Ok(())
+}
+
+fn execute_confirm_delivery(env: Env, escrow_id: u64, milestone_index: u32) -> Result<(), Error> {
+    let storage_key = get_storage_key(escrow_id);
+    let mut escrow: Escrow = env
+        .storage()
+        .persistent()
+        .get(&storage_key)
+        .ok_or(Error::EscrowNotFound)?;
+    ensure_not_paused(&env, PAUSE_RELEASES, &escrow.token_address)?;
+
+    if escrow.status != EscrowStatus::Active {
+        return Err(Error::EscrowNotActive);
+    }
+    if milestone_index >= escrow.milestone_count {
+        return Err(Error::MilestoneNotFound);
+    }
+
+    let mut milestone = load_milestone(&env, escrow_id, milestone_index)?;
+    ensure_milestone_open(&milestone)?;
+    check_approval(&milestone, false)?;
+
+    let remaining = unpaid_amount(&milestone)?;
+    milestone.status = MilestoneStatus::Released;
+    milestone.claimed = milestone.amount;
+    put_milestone(&env, escrow_id, &mut escrow, milestone_index, &milestone)?;
+
+    escrow.total_released = escrow
+        .total_released
+        .checked_add(remaining)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+
+    let token_client = token::Client::new(&env, &escrow.token_address);
+    pay_payees(&env, &token_client, &escrow, &milestone, remaining)?;
+
+    save(&env, &storage_key, &escrow);
+
+    // Standardized Event
+    publish_escrow_event(
+        &env,
+        escrow_id,
+        MilestoneReleasedEvent {
+            milestone_index,
+            payout: remaining,
+            fee: 0,
+        },
+    );
+
+    Ok(())
+}
+
+fn execute_raise_dispute(
+    env: Env,
+    escrow_id: u64,
+    caller: Address,
+    bond_payer: Address,
+) -> Result<(), Error> {
+    let storage_key = get_storage_key(escrow_id);
+    let mut escrow: Escrow = env
+        .storage()
+        .persistent()
+        .get(&storage_key)
+        .ok_or(Error::EscrowNotFound)?;
+    ensure_not_paused(&env, PAUSE_DISPUTES, &escrow.token_address)?;
+
+    if escrow.status == EscrowStatus::Disputed {
+        return Err(Error::AlreadyInDispute);
+    }
+    if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::Created {
+        return Err(Error::InvalidEscrowStatus);
+    }
+
+    let bond = VaultixEscrow::get_dispute_bond(env.clone(), escrow.token_address.clone());
+    if bond > 0 {
+        let token_client = token::Client::new(&env, &escrow.token_address);
+        token_client.transfer(&bond_payer, &env.current_contract_address(), &bond);
+    }
+
+    // An escrow-specific arbiter acts as a panel of one
+    let escrow_arbiter: Option<Address> = env
+        .storage()
+        .persistent()
+        .get(&get_escrow_arbiter_key(escrow_id));
+    let (panel, quorum) = match (escrow_arbiter, VaultixEscrow::get_panel(env.clone())) {
+        (Some(arbiter), _) => (Vec::from_array(&env, [arbiter]), 1),
+        (None, Some(p)) if !p.arbiters.is_empty() && escrow.total_amount >= p.threshold => {
+            (p.arbiters, p.quorum)
         }
+        _ => (Vec::new(&env), 0),
+    };
+
+    let dispute = Dispute {
+        raised_by: caller.clone(),
+        raised_at: env.ledger().timestamp(),
+        previous_status: escrow.status,
+        bond,
+        panel,
+        quorum,
+        votes: Vec::new(&env),
+        ruling: Resolution::None,
+        ruling_arbiters: Vec::new(&env),
+        ruled_at: 0,
+        appeal_deadline: 0,
+        appealed: false,
+        appeal_bond: 0,
+    };
+
+    // The escrow status alone freezes the milestones; they are not rewritten
+    escrow.status = EscrowStatus::Disputed;
+    escrow.resolution = Resolution::None;
+    save(&env, &storage_key, &escrow);
+    save(&env, &get_dispute_key(escrow_id), &dispute);
+
+    // Standardized Event
+    publish_escrow_event(&env, escrow_id, DisputeRaisedEvent { raised_by: caller });
 
-        escrow.milestones = updated_milestones;
-        escrow.status = EscrowStatus::Disputed;
-        escrow.resolution = Resolution::None;
-        env.storage().persistent().set(&storage_key, &escrow);
-
-        // Standardized Event
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "DisputeRaised"),
-                escrow_id,
-            ),
-            caller,
-        );
+    Ok(())
+}
 
-        Ok(())
+fn execute_assign_recipient(env: Env, escrow_id: u64, new_recipient: Address) -> Result<(), Error> {
+    let storage_key = get_storage_key(escrow_id);
+    let mut escrow: Escrow = env
+        .storage()
+        .persistent()
+        .get(&storage_key)
+        .ok_or(Error::EscrowNotFound)?;
+    ensure_not_paused(&env, PAUSE_RELEASES, &escrow.token_address)?;
+
+    if matches!(
+        escrow.status,
+        EscrowStatus::Completed
+            | EscrowStatus::Cancelled
+            | EscrowStatus::Resolved
+            | EscrowStatus::Expired
+    ) {
+        return Err(Error::InvalidEscrowStatus);
+    }
+    if new_recipient == escrow.depositor
+        || VaultixEscrow::get_escrow_arbiter(env.clone(), escrow_id) == Some(new_recipient.clone())
+    {
+        return Err(Error::SelfDealing);
     }
 
-    pub fn resolve_dispute(env: Env, escrow_id: u64, winner: Address) -> Result<(), Error> {
-        let admin = get_admin(&env)?;
-        admin.require_auth();
-
-        let storage_key = get_storage_key(escrow_id);
-        let mut escrow: Escrow = env
-            .storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)?;
+    let previous = escrow.recipient.clone();
+    escrow.recipient = new_recipient.clone();
+    save(&env, &storage_key, &escrow);
 
-        if escrow.status != EscrowStatus::Disputed {
-            return Err(Error::InvalidEscrowStatus);
+    let dispute_key = get_dispute_key(escrow_id);
+    if let Some(mut dispute) = env.storage().persistent().get::<_, Dispute>(&dispute_key) {
+        if dispute.raised_by == previous {
+            dispute.raised_by = new_recipient.clone();
+            save(&env, &dispute_key, &dispute);
         }
-        if winner != escrow.depositor && winner != escrow.recipient {
-            return Err(Error::InvalidWinner);
+    }
+    env.storage()
+        .persistent()
+        .remove(&get_settlement_key(escrow_id));
+    env.storage()
+        .persistent()
+        .remove(&get_signers_key(escrow_id, Party::Recipient));
+    for (party, action) in pending_approval_keys(&env, escrow_id).iter() {
+        if party == Party::Recipient {
+            env.storage()
+                .persistent()
+                .remove(&get_approvals_key(escrow_id, party, action.clone()));
+            track_pending_approval(&env, escrow_id, party, action, false);
         }
+    }
 
-        let outstanding = escrow
-            .total_amount
-            .checked_sub(escrow.total_released)
-            .ok_or(Error::InvalidMilestoneAmount)?;
-        let token_client = token::Client::new(&env, &escrow.token_address);
+    remove_from_paged_index(&env, symbol_short!("parties"), &previous, escrow_id);
+    add_to_paged_index(&env, symbol_short!("parties"), &new_recipient, escrow_id);
 
-        if winner == escrow.recipient {
-            let mut updated_milestones = Vec::new(&env);
-            for milestone in escrow.milestones.iter() {
-                let mut m = milestone.clone();
-                if m.status != MilestoneStatus::Released {
-                    m.status = MilestoneStatus::Released;
-                }
-                updated_milestones.push_back(m);
-            }
-            escrow.milestones = updated_milestones;
-            escrow.total_released = escrow.total_amount;
-            escrow.resolution = Resolution::Recipient;
-
-            if outstanding > 0 {
-                token_client.transfer(
-                    &env.current_contract_address(),
-                    &escrow.recipient,
-                    &outstanding,
-                );
-            }
-        } else {
-            let mut updated_milestones = Vec::new(&env);
-            for milestone in escrow.milestones.iter() {
-                let mut m = milestone.clone();
-                if m.status == MilestoneStatus::Pending || m.status == MilestoneStatus::Disputed {
-                    m.status = MilestoneStatus::Disputed;
-                }
-                updated_milestones.push_back(m);
-            }
-            escrow.milestones = updated_milestones;
-            escrow.resolution = Resolution::Depositor;
-
-            if outstanding > 0 {
-                token_client.transfer(
-                    &env.current_contract_address(),
-                    &escrow.depositor,
-                    &outstanding,
-                );
-            }
-        }
+    publish_escrow_event(
+        &env,
+        escrow_id,
+        EscrowAssignedEvent {
+            previous,
+            new_recipient,
+        },
+    );
 
-        escrow.status = EscrowStatus::Resolved;
-        env.storage().persistent().set(&storage_key, &escrow);
+    Ok(())
+}
 
-        // Standardized Event
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "DisputeResolved"),
-                escrow_id,
-            ),
-            winner,
-        );
+fn execute_propose_settlement(
+    env: Env,
+    escrow_id: u64,
+    caller: Address,
+    recipient_amount: i128,
+) -> Result<(), Error> {
+    let escrow = load_escrow(&env, escrow_id)?;
+    ensure_not_paused(&env, PAUSE_DISPUTES, &escrow.token_address)?;
+
+    if escrow.status != EscrowStatus::Disputed {
+        return Err(Error::InvalidEscrowStatus);
+    }
 
-        Ok(())
+    let dispute = VaultixEscrow::get_dispute(env.clone(), escrow_id)?;
+
+    let outstanding = disputed_balance(&escrow, &dispute)?;
+    if recipient_amount < 0 || recipient_amount > outstanding {
+        return Err(Error::InvalidSettlementAmount);
     }
 
-    pub fn cancel_escrow(env: Env, escrow_id: u64) -> Result<(), Error> {
-        let storage_key = get_storage_key(escrow_id);
-        ensure_not_paused(&env)?;
+    let proposal = SettlementProposal {
+        proposer: caller.clone(),
+        recipient_amount,
+    };
+    save(&env, &get_settlement_key(escrow_id), &proposal);
+
+    publish_escrow_event(
+        &env,
+        escrow_id,
+        SettlementProposedEvent {
+            proposer: caller,
+            recipient_amount,
+        },
+    );
 
-        let mut escrow: Escrow = env
-            .storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)?;
-        escrow.depositor.require_auth();
+    Ok(())
+}
 
-        if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::Created {
-            return Err(Error::InvalidEscrowStatus);
-        }
-        if escrow.total_released > 0 {
-            return Err(Error::MilestoneAlreadyReleased);
-        }
+fn execute_accept_settlement(
+    env: Env,
+    escrow_id: u64,
+    caller: Address,
+    recipient_amount: i128,
+) -> Result<(), Error> {
+    let storage_key = get_storage_key(escrow_id);
+    let mut escrow: Escrow = env
+        .storage()
+        .persistent()
+        .get(&storage_key)
+        .ok_or(Error::EscrowNotFound)?;
+    ensure_not_paused(&env, PAUSE_DISPUTES, &escrow.token_address)?;
 
-        if escrow.status == EscrowStatus::Active {
-            let token_client = token::Client::new(&env, &escrow.token_address);
+    if escrow.status != EscrowStatus::Disputed {
+        return Err(Error::InvalidEscrowStatus);
+    }
 
-            let refund_amount = if let Ok((treasury, fee_bps)) = Self::get_config(env.clone()) {
-                let fee = calculate_fee(escrow.total_amount, fee_bps)?;
-                if fee > 0 {
-                    token_client.transfer(&env.current_contract_address(), &treasury, &fee);
-                }
-                escrow
-                    .total_amount
-                    .checked_sub(fee)
-                    .ok_or(Error::InvalidMilestoneAmount)?
-            } else {
-                escrow.total_amount
-            };
+    let dispute = VaultixEscrow::get_dispute(env.clone(), escrow_id)?;
+    let settlement_key = get_settlement_key(escrow_id);
+    let proposal: SettlementProposal = env
+        .storage()
+        .persistent()
+        .get(&settlement_key)
+        .ok_or(Error::NoSettlementProposal)?;
 
-            if refund_amount > 0 {
-                token_client.transfer(
-                    &env.current_contract_address(),
-                    &escrow.depositor,
-                    &refund_amount,
-                );
-            }
-        }
+    if proposal.proposer == caller {
+        return Err(Error::UnauthorizedAccess);
+    }
+    if proposal.recipient_amount != recipient_amount {
+        return Err(Error::InvalidSettlementAmount);
+    }
 
-        escrow.status = EscrowStatus::Cancelled;
-        env.storage().persistent().set(&storage_key, &escrow);
-        env.storage()
-            .persistent()
-            .extend_ttl(&storage_key, 100, 2_000_000);
+    let outstanding = disputed_balance(&escrow, &dispute)?;
+    let depositor_amount = outstanding
+        .checked_sub(recipient_amount)
+        .ok_or(Error::InvalidSettlementAmount)?;
+    if depositor_amount < 0 {
+        return Err(Error::InvalidSettlementAmount);
+    }
 
-        // Standardized Event
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "EscrowCancelled"),
-                escrow_id,
-            ),
-            escrow.depositor.clone(),
+    let token_client = token::Client::new(&env, &escrow.token_address);
+    if depositor_amount > 0 {
+        token_client.transfer(
+            &env.current_contract_address(),
+            &escrow.depositor,
+            &depositor_amount,
+        );
+    }
+    // Neither side lost, so the raiser gets the bond back
+    if dispute.bond > 0 {
+        token_client.transfer(
+            &env.current_contract_address(),
+            &dispute.raised_by,
+            &dispute.bond,
         );
-
-        Ok(())
     }
 
-    pub fn complete_escrow(env: Env, escrow_id: u64) -> Result<(), Error> {
-        let storage_key = get_storage_key(escrow_id);
-        ensure_not_paused(&env)?;
+    escrow.total_released = escrow
+        .total_released
+        .checked_add(recipient_amount)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+    escrow.total_refunded = escrow
+        .total_refunded
+        .checked_add(depositor_amount)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+    escrow.resolution = Resolution::Settled;
+    escrow.status = EscrowStatus::Resolved;
+
+    env.storage().persistent().remove(&settlement_key);
+
+    publish_escrow_event(
+        &env,
+        escrow_id,
+        DisputeSettledEvent {
+            recipient_amount,
+            depositor_amount,
+        },
+    );
+
+    // The agreed split supersedes the remaining milestones
+    start_sweep(
+        &env,
+        escrow_id,
+        &mut escrow,
+        SweepAction::Payout(MilestoneStatus::Cancelled, recipient_amount, outstanding),
+    )?;
+    save(&env, &storage_key, &escrow);
 
-        let mut escrow: Escrow = env
-            .storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)?;
-        escrow.depositor.require_auth();
+    Ok(())
+}
 
-        if escrow.status != EscrowStatus::Active {
-            return Err(Error::InvalidEscrowStatus);
-        }
-        if !verify_all_released(&escrow.milestones) {
-            return Err(Error::EscrowNotActive);
-        }
+fn execute_withdraw_dispute(env: Env, escrow_id: u64, caller: Address) -> Result<(), Error> {
+    let storage_key = get_storage_key(escrow_id);
+    let mut escrow: Escrow = env
+        .storage()
+        .persistent()
+        .get(&storage_key)
+        .ok_or(Error::EscrowNotFound)?;
+    ensure_dispute_exit_open(&env, &escrow.token_address)?;
 
-        escrow.status = EscrowStatus::Completed;
-        env.storage().persistent().set(&storage_key, &escrow);
-        env.storage()
-            .persistent()
-            .extend_ttl(&storage_key, 100, 2_000_000);
+    if escrow.status != EscrowStatus::Disputed {
+        return Err(Error::InvalidEscrowStatus);
+    }
 
-        // Standardized Event
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "EscrowCompleted"),
-                escrow_id,
-            ),
-            (),
-        );
+    let dispute_key = get_dispute_key(escrow_id);
+    let dispute: Dispute = env
+        .storage()
+        .persistent()
+        .get(&dispute_key)
+        .ok_or(Error::DisputeNotFound)?;
 
-        Ok(())
+    if caller != dispute.raised_by {
+        return Err(Error::UnauthorizedAccess);
     }
 
-    pub fn refund_expired(env: Env, escrow_id: u64, caller: Address) -> Result<(), Error> {
-        let storage_key = get_storage_key(escrow_id);
+    // Withdrawing concedes the dispute, so the bond compensates the other side;
+    // an approver raises disputes on the depositor's side
+    if dispute.bond > 0 {
+        let token_client = token::Client::new(&env, &escrow.token_address);
+        token_client.transfer(
+            &env.current_contract_address(),
+            &counterparty(&escrow, &dispute_side(&escrow, &dispute.raised_by)),
+            &dispute.bond,
+        );
+    }
 
-        // Load escrow from storage
-        let mut escrow: Escrow = env
-            .storage()
-            .persistent()
-            .get(&storage_key)
-            .ok_or(Error::EscrowNotFound)?;
+    env.storage().persistent().remove(&dispute_key);
+    env.storage()
+        .persistent()
+        .remove(&get_settlement_key(escrow_id));
+
+    publish_escrow_event(
+        &env,
+        escrow_id,
+        DisputeWithdrawnEvent {
+            withdrawn_by: caller,
+        },
+    );
+
+    // The escrow stays Disputed until every milestone has been restored
+    start_sweep(
+        &env,
+        escrow_id,
+        &mut escrow,
+        SweepAction::Resume(
+            dispute.raised_at,
+            env.ledger().timestamp(),
+            dispute.previous_status,
+        ),
+    )?;
+    save(&env, &storage_key, &escrow);
 
-        // Validate deadline has passed
-        let current_time = env.ledger().timestamp();
-        if current_time <= escrow.deadline {
-            return Err(Error::DeadlineNotReached);
-        }
+    Ok(())
+}
 
-        // Validate escrow status is Active
-        if escrow.status != EscrowStatus::Active {
-            return Err(Error::InvalidStatusForRefund);
-        }
+fn execute_cancel_escrow(env: Env, escrow_id: u64) -> Result<(), Error> {
+    let storage_key = get_storage_key(escrow_id);
+    let mut escrow: Escrow = env
+        .storage()
+        .persistent()
+        .get(&storage_key)
+        .ok_or(Error::EscrowNotFound)?;
+    ensure_not_paused(&env, PAUSE_REFUNDS, &escrow.token_address)?;
 
-        // Authorization validation - only buyer can refund
-        caller.require_auth();
-        if caller != escrow.depositor {
-            return Err(Error::Unauthorized);
+    if escrow.status != EscrowStatus::Active && escrow.status != EscrowStatus::Created {
+        return Err(Error::InvalidEscrowStatus);
+    }
+    if escrow.status == EscrowStatus::Active {
+        // Funded subscriptions honour their notice period
+        if env.storage().persistent().has(&get_schedule_key(escrow_id)) {
+            return Err(Error::InvalidEscrowStatus);
         }
+        ensure_hashlocks_expired(&env, escrow_id, &escrow)?;
+    }
+    if escrow.total_released > 0 {
+        return Err(Error::MilestoneAlreadyReleased);
+    }
 
-        // Calculate remaining balance
-        let remaining_balance = escrow
-            .total_amount
-            .checked_sub(escrow.total_released)
-            .ok_or(Error::InvalidMilestoneAmount)?;
+    // Unfunded escrows hold nothing to refund
+    let close_out = if escrow.status == EscrowStatus::Active {
+        SweepAction::Refund(MilestoneStatus::Cancelled, env.ledger().timestamp())
+    } else {
+        SweepAction::Payout(MilestoneStatus::Cancelled, 0, 0)
+    };
+    escrow.status = EscrowStatus::Cancelled;
+
+    // Standardized Event
+    publish_escrow_event(
+        &env,
+        escrow_id,
+        EscrowCancelledEvent {
+            depositor: escrow.depositor.clone(),
+        },
+    );
+
+    start_sweep(&env, escrow_id, &mut escrow, close_out)?;
+    save(&env, &storage_key, &escrow);
 
-        // Check if there are funds to refund
-        if remaining_balance <= 0 {
-            return Err(Error::NoFundsToRefund);
-        }
+    Ok(())
+}
 
-        // Retrieve platform fee BPS from contract configuration
-        let (treasury, fee_bps) = Self::get_config(env.clone())?;
+fn execute_cancel_subscription(env: Env, escrow_id: u64) -> Result<(), Error> {
+    let storage_key = get_storage_key(escrow_id);
+    let mut escrow: Escrow = env
+        .storage()
+        .persistent()
+        .get(&storage_key)
+        .ok_or(Error::EscrowNotFound)?;
+    ensure_not_paused(&env, PAUSE_REFUNDS, &escrow.token_address)?;
 
-        // Calculate platform fee using checked arithmetic
-        let platform_fee = calculate_fee(remaining_balance, fee_bps)?;
+    if escrow.status != EscrowStatus::Active {
+        return Err(Error::EscrowNotActive);
+    }
 
-        // Calculate refund amount
-        let refund_amount = remaining_balance
-            .checked_sub(platform_fee)
+    let schedule_key = get_schedule_key(escrow_id);
+    let mut schedule = VaultixEscrow::get_schedule(env.clone(), escrow_id)?;
+    let cutoff = env.ledger().timestamp().saturating_add(schedule.notice);
+
+    // Unlock times never decrease, so the periods past the cutoff are the
+    // tail of the schedule
+    let mut cancelled_amount: i128 = 0;
+    let mut cancelled_periods: u32 = 0;
+    while schedule.periods > 0 && cancelled_periods < SWEEP_PAGE {
+        let index = schedule.periods - 1;
+        let mut milestone = load_milestone(&env, escrow_id, index)?;
+        let unlock_at = match milestone.kind {
+            MilestoneKind::Scheduled(unlock_at) => unlock_at,
+            _ => break,
+        };
+        if is_terminal_milestone(milestone.status) || unlock_at <= cutoff {
+            break;
+        }
+        cancelled_amount = cancelled_amount
+            .checked_add(unpaid_amount(&milestone)?)
             .ok_or(Error::InvalidMilestoneAmount)?;
+        cancelled_periods += 1;
+        milestone.status = MilestoneStatus::Cancelled;
+        put_milestone(&env, escrow_id, &mut escrow, index, &milestone)?;
+        schedule.periods = index;
+    }
+    if cancelled_amount == 0 {
+        return Err(Error::NoFundsToRefund);
+    }
 
-        // Get token client for escrow's token address
-        let token_client = token::Client::new(&env, &escrow.token_address);
-
-        // Transfer refund amount to buyer
+    let token_client = token::Client::new(&env, &escrow.token_address);
+    let (treasury, fee_bps) = VaultixEscrow::get_config(env.clone())?;
+    let fee = calculate_fee(cancelled_amount, fee_bps)?;
+    let refund_amount = cancelled_amount
+        .checked_sub(fee)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+    if fee > 0 {
+        token_client.transfer(&env.current_contract_address(), &treasury, &fee);
+    }
+    if refund_amount > 0 {
         token_client.transfer(
             &env.current_contract_address(),
             &escrow.depositor,
             &refund_amount,
         );
+    }
 
-        // If platform fee > 0, transfer fee to fee recipient
-        if platform_fee > 0 {
-            token_client.transfer(&env.current_contract_address(), &treasury, &platform_fee);
-        }
+    escrow.total_refunded = escrow
+        .total_refunded
+        .checked_add(cancelled_amount)
+        .ok_or(Error::InvalidMilestoneAmount)?;
+    save(&env, &storage_key, &escrow);
+    save(&env, &schedule_key, &schedule);
+
+    publish_escrow_event(
+        &env,
+        escrow_id,
+        SubscriptionCancelledEvent {
+            cancelled_periods,
+            refund_amount,
+            fee,
+        },
+    );
 
-        // Update escrow state
-        escrow.status = EscrowStatus::Expired;
-        escrow.total_released = escrow.total_amount;
-        env.storage().persistent().set(&storage_key, &escrow);
-        env.storage()
-            .persistent()
-            .extend_ttl(&storage_key, 100, 2_000_000);
+    Ok(())
+}
 
-        // Emit RefundEvent
-        env.events().publish(
-            (
-                Symbol::new(&env, "Vaultix"),
-                Symbol::new(&env, "RefundExpired"),
-                escrow_id,
-            ),
-            (escrow.depositor.clone(), refund_amount, current_time),
-        );
+fn execute_reject_milestone(
+    env: Env,
+    escrow_id: u64,
+    milestone_index: u32,
+    reason: Symbol,
+) -> Result<(), Error> {
+    let storage_key = get_storage_key(escrow_id);
+    let mut escrow: Escrow = env
+        .storage()
+        .persistent()
+        .get(&storage_key)
+        .ok_or(Error::EscrowNotFound)?;
+    ensure_not_paused(&env, PAUSE_RELEASES, &escrow.token_address)?;
 
-        Ok(())
+    if escrow.status != EscrowStatus::Active {
+        return Err(Error::EscrowNotActive);
     }
-}
 
-fn get_storage_key(escrow_id: u64) -> (Symbol, u64) {
-    (symbol_short!("escrow"), escrow_id)
+    let mut milestone = load_milestone(&env, escrow_id, milestone_index)?;
+    if milestone.status != MilestoneStatus::Submitted {
+        return Err(Error::InvalidMilestoneStatus);
+    }
+
+    milestone.status = MilestoneStatus::Pending;
+    milestone.submitted_at = 0;
+    put_milestone(&env, escrow_id, &mut escrow, milestone_index, &milestone)?;
+
+    save(&env, &storage_key, &escrow);
+    env.storage()
+        .persistent()
+        .remove(&get_proof_key(escrow_id, milestone_index));
+
+    publish_escrow_event(
+        &env,
+        escrow_id,
+        MilestoneRejectedEvent {
+            milestone_index,
+            reason,
+        },
+    );
+
+    Ok(())
 }
 
-fn ensure_not_paused(env: &Env) -> Result<(), Error> {
-    let state: ContractState = env
+fn execute_appeal_ruling(
+    env: Env,
+    escrow_id: u64,
+    caller: Address,
+    bond_payer: Address,
+) -> Result<(), Error> {
+    let storage_key = get_storage_key(escrow_id);
+    let mut escrow: Escrow = env
         .storage()
-        .instance()
-        .get(&symbol_short!("state"))
-        .unwrap_or(ContractState::Active);
-    if state == ContractState::Paused {
-        return Err(Error::ContractPaused);
+        .persistent()
+        .get(&storage_key)
+        .ok_or(Error::EscrowNotFound)?;
+    ensure_not_paused(&env, PAUSE_DISPUTES, &escrow.token_address)?;
+
+    if escrow.status != EscrowStatus::RulingPending {
+        return Err(Error::InvalidEscrowStatus);
+    }
+
+    let dispute_key = get_dispute_key(escrow_id);
+    let mut dispute = VaultixEscrow::get_dispute(env.clone(), escrow_id)?;
+    let winner = ruling_winner(&escrow, dispute.ruling)?;
+    if caller == winner {
+        return Err(Error::UnauthorizedAccess);
+    }
+
+    if env.ledger().timestamp() > dispute.appeal_deadline {
+        return Err(Error::AppealWindowClosed);
+    }
+    let config = VaultixEscrow::get_appeal_config(env.clone()).ok_or(Error::AppealsDisabled)?;
+
+    if config.bond > 0 {
+        let token_client = token::Client::new(&env, &escrow.token_address);
+        token_client.transfer(&bond_payer, &env.current_contract_address(), &config.bond);
     }
+
+    dispute.appealed = true;
+    dispute.appeal_bond = config.bond;
+    dispute.ruling_arbiters = Vec::from_array(&env, [config.arbiter.clone()]);
+    save(&env, &dispute_key, &dispute);
+
+    escrow.status = EscrowStatus::Appealed;
+    save(&env, &storage_key, &escrow);
+
+    publish_escrow_event(
+        &env,
+        escrow_id,
+        RulingAppealedEvent {
+            appellant: caller,
+            bond: config.bond,
+            arbiter: config.arbiter,
+        },
+    );
+
     Ok(())
 }
 
-fn admin_storage_key() -> Symbol {
-    symbol_short!("admin")
-}
+fn execute_refund_expired(env: Env, escrow_id: u64) -> Result<(), Error> {
+    let storage_key = get_storage_key(escrow_id);
 
-fn get_admin(env: &Env) -> Result<Address, Error> {
-    env.storage()
+    // Load escrow from storage
+    let mut escrow: Escrow = env
+        .storage()
         .persistent()
-        .get(&admin_storage_key())
-        .ok_or(Error::AdminNotInitialized)
-}
-
-fn validate_milestones(milestones: &Vec<Milestone>) -> Result<i128, Error> {
-    if milestones.len() > 20 {
-        return Err(Error::VectorTooLarge);
+        .get(&storage_key)
+        .ok_or(Error::EscrowNotFound)?;
+    ensure_not_paused(&env, PAUSE_REFUNDS, &escrow.token_address)?;
+
+    // Validate deadline has passed
+    let current_time = env.ledger().timestamp();
+    if current_time <= escrow.deadline {
+        return Err(Error::DeadlineNotReached);
     }
-    let mut total: i128 = 0;
-    for milestone in milestones.iter() {
-        if milestone.amount <= 0 {
-            return Err(Error::ZeroAmount);
-        }
-        total = total
-            .checked_add(milestone.amount)
-            .ok_or(Error::InvalidMilestoneAmount)?;
+
+    // Validate escrow status is Active
+    if escrow.status != EscrowStatus::Active {
+        return Err(Error::InvalidStatusForRefund);
     }
-    Ok(total)
-}
 
-fn verify_all_released(milestones: &Vec<Milestone>) -> bool {
-    for milestone in milestones.iter() {
-        if milestone.status != MilestoneStatus::Released {
-            return false;
-        }
+    // Check if there are funds to refund
+    if outstanding_balance(&escrow)? <= 0 {
+        return Err(Error::NoFundsToRefund);
     }
-    true
-}
 
-/// Calculate platform fee using basis points (BPS)
-/// Formula: fee = (amount * fee_bps) / 10000
-/// Uses checked arithmetic to prevent overflow
-fn calculate_fee(amount: i128, fee_bps: i128) -> Result<i128, Error> {
-    // Multiply amount by fee basis points with overflow protection
-    let fee_numerator = amount
-        .checked_mul(fee_bps)
-        .ok_or(Error::InvalidMilestoneAmount)?;
+    // Refunds are paid milestone by milestone; `RefundExpired` is
+    // published once the last one is closed
+    escrow.status = EscrowStatus::Expired;
+    start_sweep(
+        &env,
+        escrow_id,
+        &mut escrow,
+        SweepAction::Refund(MilestoneStatus::Refunded, current_time),
+    )?;
+    save(&env, &storage_key, &escrow);
 
-    // Divide by BPS denominator (10000) to get final fee
-    let fee = fee_numerator
-        .checked_div(BPS_DENOMINATOR)
-        .ok_or(Error::InvalidMilestoneAmount)?;
+    Ok(())
+}
 
-    Ok(fee)
+/// Rejects cancellation while an open hash-locked milestone can still be
+/// released with its preimage.
+fn ensure_hashlocks_expired(env: &Env, escrow_id: u64, escrow: &Escrow) -> Result<(), Error> {
+    let now = env.ledger().timestamp();
+    if now > escrow.hashlock_until {
+        return Ok(());
+    }
+    for index in 0..escrow.milestone_count {
+        let milestone = load_milestone(env, escrow_id, index)?;
+        if let ReleaseCondition::HashLock(_, timelock) = milestone.condition {
+            if now <= timelock && !is_terminal_milestone(milestone.status) {
+                return Err(Error::HashLockActive);
+            }
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -1,7 +1,9 @@
 use super::*;
 use soroban_sdk::{
     testutils::{Address as _, Events, Ledger},
-    token, vec, Address, Env, IntoVal,
+    token, vec,
+    xdr::{self, ToXdr},
+    Address, Bytes, BytesN, Env, IntoVal, Map, String, TryFromVal, Val,
 };
 
 /// Helper function to create and initialize a test token
@@ -48,6 +50,15 @@ fn test_create_escrow_fails_when_paused() {
             amount: 10_000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -65,6 +76,55 @@ fn test_create_escrow_fails_when_paused() {
     assert_eq!(result, Err(Ok(Error::ContractPaused)));
 }
 
+#[test]
+fn test_legacy_paused_state_survives_upgrade() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &None);
+
+    // A deployment paused by an earlier release only has the `state` key
+    env.as_contract(&contract_id, || {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("state"), &ContractState::Paused);
+    });
+    assert_eq!(client.get_pause_flags(), PAUSE_ALL);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+    let result = client.try_create_escrow(
+        &1_006u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &work_milestones(&env),
+        &1_706_400_000u64,
+    );
+    assert_eq!(result, Err(Ok(Error::ContractPaused)));
+
+    // Writing the flags replaces the legacy switch
+    client.set_paused(&false);
+    assert_eq!(client.get_pause_flags(), 0);
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().instance().has(&symbol_short!("state")));
+    });
+    client.create_escrow(
+        &1_006u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &work_milestones(&env),
+        &1_706_400_000u64,
+    );
+}
+
 #[test]
 fn test_deposit_funds_fails_when_paused() {
     let env = Env::default();
@@ -90,6 +150,15 @@ fn test_deposit_funds_fails_when_paused() {
             amount: 10_000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -110,6 +179,209 @@ fn test_deposit_funds_fails_when_paused() {
     assert_eq!(result, Err(Ok(Error::ContractPaused)));
 }
 
+/// A single 10_000 milestone, for the pause tests.
+fn work_milestones(env: &Env) -> soroban_sdk::Vec<Milestone> {
+    vec![
+        env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(env),
+        },
+    ]
+}
+
+#[test]
+fn test_pause_flags_by_operation_and_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let deadline = 1_706_400_000u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    let (_, _, other_token) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+
+    // Releases paused globally: new escrows and deposits still go through
+    client.set_pause_flags(&PAUSE_RELEASES);
+    assert_eq!(client.get_pause_flags(), PAUSE_RELEASES);
+    client.create_escrow(
+        &1_002u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &work_milestones(&env),
+        &deadline,
+    );
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&1_002u64);
+    assert_eq!(
+        client.try_release_milestone(&1_002u64, &0),
+        Err(Ok(Error::ContractPaused))
+    );
+    client.raise_dispute(&1_002u64, &depositor);
+    client.withdraw_dispute(&1_002u64, &depositor);
+
+    client.set_pause_flags(&0);
+    client.release_milestone(&1_002u64, &0);
+    assert_eq!(token_client.balance(&recipient), 10_000);
+
+    // New escrows paused for one token only
+    client.set_token_pause_flags(&token_address, &PAUSE_NEW_ESCROWS);
+    assert_eq!(
+        client.get_token_pause_flags(&token_address),
+        PAUSE_NEW_ESCROWS
+    );
+    let result = client.try_create_escrow(
+        &1_003u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &work_milestones(&env),
+        &deadline,
+    );
+    assert_eq!(result, Err(Ok(Error::ContractPaused)));
+    client.create_escrow(
+        &1_003u64,
+        &depositor,
+        &recipient,
+        &other_token,
+        &work_milestones(&env),
+        &deadline,
+    );
+
+    client.set_token_pause_flags(&token_address, &0);
+    let event = env.events().all().last().unwrap();
+    let expected_topics: soroban_sdk::Vec<Val> = (
+        Symbol::new(&env, "Vaultix"),
+        Symbol::new(&env, "TokenPauseUpdated"),
+        EVENT_VERSION,
+    )
+        .into_val(&env);
+    assert_eq!(event.1, expected_topics);
+    let payload: TokenPauseUpdatedEvent = event.2.into_val(&env);
+    assert_eq!(payload.token, token_address);
+    assert_eq!(payload.flags, 0);
+    assert_eq!(client.get_token_pause_flags(&token_address), 0);
+
+    // Unknown bits are rejected
+    assert_eq!(
+        client.try_set_pause_flags(&(PAUSE_ALL + 1)),
+        Err(Ok(Error::InvalidFeeConfiguration))
+    );
+    assert_eq!(
+        client.try_set_token_pause_flags(&token_address, &(PAUSE_ALL + 1)),
+        Err(Ok(Error::InvalidFeeConfiguration))
+    );
+}
+
+#[test]
+fn test_emergency_mode_keeps_exits_open() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let deadline = 1_706_400_000u64;
+
+    let secondary = Address::generate(&env);
+    client.init(&admin);
+    client.set_appeal_config(&86_400, &0, &secondary);
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &40_000);
+    token_client.approve(&depositor, &contract_id, &40_000, &200);
+
+    for escrow_id in [1_004u64, 1_005u64, 1_006u64, 1_007u64] {
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &work_milestones(&env),
+            &deadline,
+        );
+        client.deposit_funds(&escrow_id);
+    }
+    client.raise_dispute(&1_006u64, &depositor);
+    client.raise_dispute(&1_007u64, &depositor);
+    client.resolve_dispute(&1_007u64, &recipient);
+
+    // Everything paused, refunds included, then emergency mode on top
+    client.set_paused(&true);
+    client.set_emergency_mode(&true);
+    assert!(client.is_emergency_mode());
+
+    let event = env.events().all().last().unwrap();
+    let expected_topics: soroban_sdk::Vec<Val> = (
+        Symbol::new(&env, "Vaultix"),
+        Symbol::new(&env, "EmergencyModeChanged"),
+        EVENT_VERSION,
+    )
+        .into_val(&env);
+    assert_eq!(event.1, expected_topics);
+    let payload: EmergencyModeChangedEvent = event.2.into_val(&env);
+    assert_eq!(
+        payload,
+        EmergencyModeChangedEvent {
+            enabled: true,
+            treasury: treasury.clone(),
+        }
+    );
+
+    assert_eq!(
+        client.try_release_milestone(&1_004u64, &0),
+        Err(Ok(Error::ContractPaused))
+    );
+    assert_eq!(
+        client.try_raise_dispute(&1_004u64, &depositor),
+        Err(Ok(Error::ContractPaused))
+    );
+
+    // Exits back to the depositor stay open
+    client.cancel_escrow(&1_004u64);
+    env.ledger().with_mut(|li| li.timestamp = deadline + 1);
+    client.refund_expired(&1_005u64, &depositor);
+    assert_eq!(token_client.balance(&depositor), 20_000);
+
+    // So does winding disputes down, though a ruling still waits out its appeal window
+    client.withdraw_dispute(&1_006u64, &depositor);
+    assert_eq!(client.get_state(&1_006u64), EscrowStatus::Active);
+    client.execute_ruling(&1_007u64);
+    assert_eq!(client.get_state(&1_007u64), EscrowStatus::Resolved);
+    assert_eq!(token_client.balance(&recipient), 10_000);
+    assert_eq!(token_client.balance(&contract_id), 10_000);
+
+    // Leaving emergency mode falls back to the flags, which still pause refunds
+    client.set_emergency_mode(&false);
+    assert_eq!(client.get_pause_flags(), PAUSE_ALL);
+}
+
 #[test]
 fn test_create_and_get_escrow() {
     let env = Env::default();
@@ -133,16 +405,43 @@ fn test_create_and_get_escrow() {
             amount: 3000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Design"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
         Milestone {
             amount: 3000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Dev"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
         Milestone {
             amount: 4000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Deploy"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -164,7 +463,7 @@ fn test_create_and_get_escrow() {
     assert_eq!(escrow.total_amount, 10000);
     assert_eq!(escrow.total_released, 0);
     assert_eq!(escrow.status, EscrowStatus::Created);
-    assert_eq!(escrow.milestones.len(), 3);
+    assert_eq!(escrow.milestone_count, 3);
 
     // Verify Create Event (Refactored Schema)
     let events = env.events().all();
@@ -175,22 +474,31 @@ fn test_create_and_get_escrow() {
     let expected_topics: soroban_sdk::Vec<soroban_sdk::Val> = (
         Symbol::new(&env, "Vaultix"),
         Symbol::new(&env, "EscrowCreated"),
+        EVENT_VERSION,
         escrow_id,
     )
         .into_val(&env);
     assert_eq!(event.1, expected_topics);
 
-    // Payload assertion: Convert event.2 into a Vec<Val> and compare with expected Vec<Val>
-    let actual_payload: soroban_sdk::Vec<soroban_sdk::Val> = event.2.into_val(&env);
-    let expected_payload: soroban_sdk::Vec<soroban_sdk::Val> = vec![
-        &env,
-        depositor.clone().into_val(&env),
-        recipient.clone().into_val(&env),
-        token_address.clone().into_val(&env),
-        10000i128.into_val(&env),
-        deadline.into_val(&env),
-    ];
-    assert_eq!(actual_payload, expected_payload);
+    // Payload assertion: the escrow's first event, carrying the typed struct
+    let (sequence, payload): (u64, EscrowCreatedEvent) = event.2.into_val(&env);
+    assert_eq!(sequence, 1);
+    assert_eq!(
+        payload,
+        EscrowCreatedEvent {
+            depositor: depositor.clone(),
+            recipient: recipient.clone(),
+            token_address: token_address.clone(),
+            total_amount: 10000,
+            deadline,
+            milestones: vec![
+                &env,
+                MilestoneMetadata::empty(&env),
+                MilestoneMetadata::empty(&env),
+                MilestoneMetadata::empty(&env),
+            ],
+        }
+    );
 
     assert_eq!(escrow.deadline, deadline);
 
@@ -224,11 +532,29 @@ fn test_deposit_funds() {
             amount: 5000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Phase1"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
         Milestone {
             amount: 5000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Phase2"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -286,11 +612,29 @@ fn test_release_milestone_with_tokens() {
             amount: 6000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Phase1"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
         Milestone {
             amount: 4000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Phase2"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -321,11 +665,11 @@ fn test_release_milestone_with_tokens() {
     let escrow = client.get_escrow(&escrow_id);
     assert_eq!(escrow.total_released, 6000);
     assert_eq!(
-        escrow.milestones.get(0).unwrap().status,
+        client.get_milestone(&escrow_id, &0).status,
         MilestoneStatus::Released
     );
     assert_eq!(
-        escrow.milestones.get(1).unwrap().status,
+        client.get_milestone(&escrow_id, &1).status,
         MilestoneStatus::Pending
     );
 
@@ -356,6 +700,15 @@ fn test_dispute_blocks_release() {
             amount: 500,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -402,11 +755,29 @@ fn test_complete_escrow_with_all_releases() {
             amount: 5000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task1"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
         Milestone {
             amount: 5000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task2"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -460,6 +831,15 @@ fn test_cancel_escrow_with_refund() {
             amount: 10000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -511,6 +891,15 @@ fn test_cancel_unfunded_escrow() {
             amount: 5000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -529,6 +918,10 @@ fn test_cancel_unfunded_escrow() {
 
     let escrow = client.get_escrow(&escrow_id);
     assert_eq!(escrow.status, EscrowStatus::Cancelled);
+    assert!(client
+        .get_milestones(&escrow_id, &0, &escrow.milestone_count)
+        .iter()
+        .all(|m| m.status == MilestoneStatus::Cancelled));
 }
 
 #[test]
@@ -555,11 +948,29 @@ fn test_admin_resolves_dispute_to_recipient() {
             amount: 4000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Phase1"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
         Milestone {
             amount: 6000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Phase2"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -583,8 +994,8 @@ fn test_admin_resolves_dispute_to_recipient() {
     assert_eq!(escrow.status, EscrowStatus::Resolved);
     assert_eq!(escrow.resolution, Resolution::Recipient);
     assert_eq!(escrow.total_released, escrow.total_amount);
-    assert!(escrow
-        .milestones
+    assert!(client
+        .get_milestones(&escrow_id, &0, &escrow.milestone_count)
         .iter()
         .all(|m| m.status == MilestoneStatus::Released));
 
@@ -617,11 +1028,29 @@ fn test_admin_resolves_dispute_to_depositor() {
             amount: 2000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Alpha"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
         Milestone {
             amount: 3000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Beta"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -645,10 +1074,10 @@ fn test_admin_resolves_dispute_to_depositor() {
     assert_eq!(escrow.status, EscrowStatus::Resolved);
     assert_eq!(escrow.resolution, Resolution::Depositor);
     assert_eq!(escrow.total_released, 0);
-    assert!(escrow
-        .milestones
+    assert!(client
+        .get_milestones(&escrow_id, &0, &escrow.milestone_count)
         .iter()
-        .all(|m| m.status == MilestoneStatus::Disputed));
+        .all(|m| m.status == MilestoneStatus::Refunded));
 
     assert_eq!(token_client.balance(&depositor), 5000);
     assert_eq!(token_client.balance(&contract_id), 0);
@@ -678,6 +1107,15 @@ fn test_duplicate_escrow_id() {
             amount: 1000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Test"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -725,6 +1163,15 @@ fn test_double_release() {
             amount: 1000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -770,6 +1217,15 @@ fn test_too_many_milestones() {
             amount: 100,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         });
     }
 
@@ -806,6 +1262,15 @@ fn test_invalid_milestone_amount() {
             amount: 0, // Invalid: zero amount
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -843,6 +1308,15 @@ fn test_unauthorized_confirm_delivery() {
             amount: 1000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -884,6 +1358,15 @@ fn test_double_confirm_delivery() {
             amount: 1000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -925,6 +1408,15 @@ fn test_zero_amount_milestone_rejected() {
             amount: 0,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Test"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -961,6 +1453,15 @@ fn test_negative_amount_milestone_rejected() {
             amount: -1000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Test"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -996,6 +1497,15 @@ fn test_self_dealing_rejected() {
             amount: 5000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -1032,11 +1542,29 @@ fn test_valid_escrow_creation_succeeds() {
             amount: 3000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Phase1"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
         Milestone {
             amount: 7000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Phase2"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -1082,6 +1610,15 @@ fn test_double_deposit_rejected() {
             amount: 5000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -1126,6 +1663,15 @@ fn test_cancel_active_escrow_retains_fee() {
             amount: 10_000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -1179,6 +1725,15 @@ fn test_release_milestone_before_deposit() {
             amount: 5000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -1223,6 +1778,15 @@ fn test_refund_expired_authorization_check() {
             amount: 10_000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -1277,11 +1841,29 @@ fn test_refund_expired_no_funds_check() {
             amount: 5_000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task1"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
         Milestone {
             amount: 5_000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task2"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -1336,11 +1918,29 @@ fn test_refund_expired_with_partial_releases() {
             amount: 6_000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task1"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
         Milestone {
             amount: 4_000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Task2"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -1370,6 +1970,18 @@ fn test_refund_expired_with_partial_releases() {
     // Refund should succeed with remaining balance (4_000)
     let result = client.try_refund_expired(&escrow_id, &depositor);
     assert!(result.is_ok());
+
+    // Paid milestone stays released, the unpaid one is marked refunded
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.open_milestones, 0);
+    assert_eq!(
+        client.get_milestone(&escrow_id, &0).status,
+        MilestoneStatus::Released
+    );
+    assert_eq!(
+        client.get_milestone(&escrow_id, &1).status,
+        MilestoneStatus::Refunded
+    );
 }
 
 #[test]
@@ -1398,6 +2010,15 @@ fn test_refund_expired_successful_basic() {
             amount: 10_000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -1434,7 +2055,8 @@ fn test_refund_expired_successful_basic() {
     // Verify escrow status updated to Expired
     let escrow = client.get_escrow(&escrow_id);
     assert_eq!(escrow.status, EscrowStatus::Expired);
-    assert_eq!(escrow.total_released, escrow.total_amount);
+    assert_eq!(escrow.total_released, 0);
+    assert_eq!(escrow.total_refunded, escrow.total_amount);
 }
 
 #[test]
@@ -1462,6 +2084,15 @@ fn test_refund_expired_deadline_not_reached() {
             amount: 10_000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -1518,6 +2149,15 @@ fn test_refund_expired_invalid_status() {
             amount: 10_000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -1594,6 +2234,15 @@ fn test_refund_expired_fee_calculation_edge_cases() {
                 amount: 10_000,
                 status: MilestoneStatus::Pending,
                 description: symbol_short!("Work"),
+                due_date: 0,
+                review_period: 0,
+                submitted_at: 0,
+                kind: MilestoneKind::Fixed,
+                claimed: 0,
+                condition: ReleaseCondition::None,
+                payees: vec![&env],
+                approval: ApprovalRule::Depositor,
+                metadata: MilestoneMetadata::empty(&env),
             },
         ];
 
@@ -1636,6 +2285,15 @@ fn test_refund_expired_fee_calculation_edge_cases() {
                 amount: 10_000,
                 status: MilestoneStatus::Pending,
                 description: symbol_short!("Work"),
+                due_date: 0,
+                review_period: 0,
+                submitted_at: 0,
+                kind: MilestoneKind::Fixed,
+                claimed: 0,
+                condition: ReleaseCondition::None,
+                payees: vec![&env],
+                approval: ApprovalRule::Depositor,
+                metadata: MilestoneMetadata::empty(&env),
             },
         ];
 
@@ -1681,6 +2339,15 @@ fn test_refund_expired_fee_calculation_edge_cases() {
                 amount: 100,
                 status: MilestoneStatus::Pending,
                 description: symbol_short!("Work"),
+                due_date: 0,
+                review_period: 0,
+                submitted_at: 0,
+                kind: MilestoneKind::Fixed,
+                claimed: 0,
+                condition: ReleaseCondition::None,
+                payees: vec![&env],
+                approval: ApprovalRule::Depositor,
+                metadata: MilestoneMetadata::empty(&env),
             },
         ];
 
@@ -1734,6 +2401,15 @@ fn test_refund_expired_disputed_status() {
             amount: 10_000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -1786,6 +2462,15 @@ fn test_refund_expired_completed_status() {
             amount: 10_000,
             status: MilestoneStatus::Pending,
             description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
         },
     ];
 
@@ -1813,3 +2498,4022 @@ fn test_refund_expired_completed_status() {
     let result = client.try_refund_expired(&escrow_id, &depositor);
     assert_eq!(result, Err(Ok(Error::InvalidStatusForRefund)));
 }
+
+#[test]
+fn test_mutual_settlement_splits_outstanding_balance() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 400u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 4_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase1"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+        Milestone {
+            amount: 6_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase2"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    client.raise_dispute(&escrow_id, &depositor);
+
+    // Amounts above the disputed balance are rejected
+    let result = client.try_propose_settlement(&escrow_id, &recipient, &10_001);
+    assert_eq!(result, Err(Ok(Error::InvalidSettlementAmount)));
+
+    client.propose_settlement(&escrow_id, &recipient, &7_000);
+
+    // The proposer cannot accept their own offer
+    let result = client.try_accept_settlement(&escrow_id, &recipient, &7_000);
+    assert_eq!(result, Err(Ok(Error::UnauthorizedAccess)));
+
+    // The accepted amount must match the stored proposal
+    let result = client.try_accept_settlement(&escrow_id, &depositor, &5_000);
+    assert_eq!(result, Err(Ok(Error::InvalidSettlementAmount)));
+
+    client.accept_settlement(&escrow_id, &depositor, &7_000);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Resolved);
+    assert_eq!(escrow.resolution, Resolution::Settled);
+    assert_eq!(escrow.total_released, 7_000);
+    assert!(client
+        .get_milestones(&escrow_id, &0, &escrow.milestone_count)
+        .iter()
+        .all(|m| m.status == MilestoneStatus::Cancelled));
+
+    assert_eq!(token_client.balance(&recipient), 7_000);
+    assert_eq!(token_client.balance(&depositor), 3_000);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_accept_settlement_without_proposal_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 401u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &1_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // No dispute yet
+    let result = client.try_propose_settlement(&escrow_id, &depositor, &500);
+    assert_eq!(result, Err(Ok(Error::InvalidEscrowStatus)));
+
+    client.raise_dispute(&escrow_id, &recipient);
+
+    let result = client.try_accept_settlement(&escrow_id, &depositor, &500);
+    assert_eq!(result, Err(Ok(Error::NoSettlementProposal)));
+}
+
+#[test]
+fn test_withdraw_dispute_restores_active_escrow() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 402u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &1_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    client.raise_dispute(&escrow_id, &recipient);
+
+    // Only the party that raised the dispute may withdraw it
+    let result = client.try_withdraw_dispute(&escrow_id, &depositor);
+    assert_eq!(result, Err(Ok(Error::UnauthorizedAccess)));
+
+    client.withdraw_dispute(&escrow_id, &recipient);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Active);
+    assert_eq!(
+        client.get_milestone(&escrow_id, &0).status,
+        MilestoneStatus::Pending
+    );
+    assert!(client.try_get_dispute(&escrow_id).is_err());
+
+    // Normal flow continues after the dispute is dropped
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&recipient), 1_000);
+}
+
+#[test]
+fn test_dispute_bond_forfeited_and_arbiter_fee_paid() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let escrow_id = 410u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+    token_admin.mint(&recipient, &500);
+
+    client.init(&admin);
+    client.set_dispute_bond(&token_address, &500);
+    client.set_arbiter_fee(&100); // 1%
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // Recipient posts the bond when disputing
+    client.raise_dispute(&escrow_id, &recipient);
+    assert_eq!(token_client.balance(&recipient), 0);
+    assert_eq!(token_client.balance(&contract_id), 10_500);
+    assert_eq!(client.get_dispute(&escrow_id).bond, 500);
+
+    // Recipient loses: depositor gets the balance minus the arbiter fee plus the bond
+    client.resolve_dispute(&escrow_id, &depositor);
+
+    assert_eq!(token_client.balance(&admin), 100);
+    assert_eq!(token_client.balance(&depositor), 9_900 + 500);
+    assert_eq!(token_client.balance(&recipient), 0);
+    assert_eq!(token_client.balance(&contract_id), 0);
+
+    let events = env.events().all();
+    let event = events.last().unwrap();
+    let expected_topics: soroban_sdk::Vec<soroban_sdk::Val> = (
+        Symbol::new(&env, "Vaultix"),
+        Symbol::new(&env, "DisputeResolved"),
+        EVENT_VERSION,
+        escrow_id,
+    )
+        .into_val(&env);
+    assert_eq!(event.1, expected_topics);
+
+    let (_, payload): (u64, DisputeResolvedEvent) = event.2.into_val(&env);
+    assert_eq!(
+        payload,
+        DisputeResolvedEvent {
+            winner: depositor.clone(),
+            payout: 9_900,
+            arbiter_fee: 100,
+            bond: 500,
+            bond_recipient: depositor.clone(),
+        }
+    );
+}
+
+#[test]
+fn test_dispute_bond_returned_to_winning_raiser() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let escrow_id = 411u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &5_000);
+    token_admin.mint(&recipient, &200);
+
+    client.init(&admin);
+    client.set_dispute_bond(&token_address, &200);
+    assert_eq!(client.get_dispute_bond(&token_address), 200);
+
+    let result = client.try_set_dispute_bond(&token_address, &-1);
+    assert_eq!(result, Err(Ok(Error::InvalidBondAmount)));
+    let result = client.try_set_arbiter_fee(&10_001);
+    assert_eq!(result, Err(Ok(Error::InvalidFeeConfiguration)));
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 5_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &5_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    client.raise_dispute(&escrow_id, &recipient);
+    client.resolve_dispute(&escrow_id, &recipient);
+
+    // No arbiter fee configured, bond comes back to the winning raiser
+    assert_eq!(token_client.balance(&recipient), 5_200);
+    assert_eq!(token_client.balance(&admin), 0);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_panel_quorum_executes_ruling() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let arbiter1 = Address::generate(&env);
+    let arbiter2 = Address::generate(&env);
+    let arbiter3 = Address::generate(&env);
+    let substitute = Address::generate(&env);
+    let escrow_id = 420u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+
+    client.init(&admin);
+    client.set_arbiter_fee(&100); // 1%
+
+    // Quorum cannot exceed the panel size
+    let result =
+        client.try_configure_panel(&vec![&env, arbiter1.clone(), arbiter2.clone()], &3, &5_000);
+    assert_eq!(result, Err(Ok(Error::InvalidPanelConfiguration)));
+
+    client.configure_panel(
+        &vec![&env, arbiter1.clone(), arbiter2.clone(), arbiter3.clone()],
+        &2,
+        &5_000,
+    );
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    client.raise_dispute(&escrow_id, &recipient);
+
+    let dispute = client.get_dispute(&escrow_id);
+    assert_eq!(dispute.panel.len(), 3);
+    assert_eq!(dispute.quorum, 2);
+
+    // High-value disputes bypass the single arbiter
+    let result = client.try_resolve_dispute(&escrow_id, &recipient);
+    assert_eq!(result, Err(Ok(Error::PanelRequired)));
+
+    let result = client.try_cast_arbiter_vote(&escrow_id, &substitute, &Ruling::Recipient);
+    assert_eq!(result, Err(Ok(Error::NotPanelMember)));
+
+    client.cast_arbiter_vote(&escrow_id, &arbiter1, &Ruling::Recipient);
+    client.cast_arbiter_vote(&escrow_id, &arbiter2, &Ruling::Abstain);
+    client.cast_arbiter_vote(&escrow_id, &arbiter3, &Ruling::Depositor);
+    assert_eq!(client.get_escrow(&escrow_id).status, EscrowStatus::Disputed);
+    assert_eq!(client.get_dispute(&escrow_id).votes.len(), 3);
+
+    // Replacing a member drops their vote
+    client.replace_arbiter(&escrow_id, &arbiter3, &substitute);
+    let dispute = client.get_dispute(&escrow_id);
+    assert_eq!(dispute.votes.len(), 2);
+    assert!(dispute.panel.contains(&substitute));
+    assert!(!dispute.panel.contains(&arbiter3));
+
+    // Second matching vote reaches the quorum and pays out
+    client.cast_arbiter_vote(&escrow_id, &substitute, &Ruling::Recipient);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Resolved);
+    assert_eq!(escrow.resolution, Resolution::Recipient);
+
+    // Arbiter fee is shared by the members who voted for the ruling
+    assert_eq!(token_client.balance(&recipient), 9_900);
+    assert_eq!(token_client.balance(&arbiter1), 50);
+    assert_eq!(token_client.balance(&substitute), 50);
+    assert_eq!(token_client.balance(&arbiter2), 0);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_low_value_dispute_uses_admin_arbiter() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let arbiter1 = Address::generate(&env);
+    let arbiter2 = Address::generate(&env);
+    let escrow_id = 421u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1_000);
+
+    client.init(&admin);
+    client.configure_panel(&vec![&env, arbiter1, arbiter2.clone()], &2, &5_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &1_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    client.raise_dispute(&escrow_id, &depositor);
+
+    let dispute = client.get_dispute(&escrow_id);
+    assert!(dispute.panel.is_empty());
+
+    let result = client.try_cast_arbiter_vote(&escrow_id, &arbiter2, &Ruling::Depositor);
+    assert_eq!(result, Err(Ok(Error::NotPanelMember)));
+
+    client.resolve_dispute(&escrow_id, &depositor);
+    assert_eq!(token_client.balance(&depositor), 1_000);
+}
+
+#[test]
+fn test_ruling_executes_after_appeal_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let secondary = Address::generate(&env);
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let escrow_id = 430u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &5_000);
+
+    client.init(&admin);
+    client.set_appeal_config(&86_400, &0, &secondary);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 5_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &5_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    client.raise_dispute(&escrow_id, &depositor);
+    client.resolve_dispute(&escrow_id, &recipient);
+
+    // Ruling is recorded but funds stay locked
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::RulingPending);
+    assert_eq!(client.get_dispute(&escrow_id).ruling, Resolution::Recipient);
+    assert_eq!(token_client.balance(&contract_id), 5_000);
+
+    assert_eq!(
+        client.get_dispute(&escrow_id).appeal_deadline,
+        1_000 + 86_400
+    );
+
+    // The deadline is fixed at ruling time, so shortening the window later
+    // cannot cut the appeal short
+    client.set_appeal_config(&1, &0, &secondary);
+    env.ledger().with_mut(|li| li.timestamp = 2_000);
+    let result = client.try_execute_ruling(&escrow_id);
+    assert_eq!(result, Err(Ok(Error::AppealWindowOpen)));
+
+    // The winning side cannot appeal
+    let result = client.try_appeal_ruling(&escrow_id, &recipient);
+    assert_eq!(result, Err(Ok(Error::UnauthorizedAccess)));
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000 + 86_401);
+
+    // Nor does lengthening it reopen a lapsed appeal
+    client.set_appeal_config(&1_000_000, &0, &secondary);
+    let result = client.try_appeal_ruling(&escrow_id, &depositor);
+    assert_eq!(result, Err(Ok(Error::AppealWindowClosed)));
+
+    // Anyone can execute once the window has lapsed
+    client.execute_ruling(&escrow_id);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Resolved);
+    assert_eq!(escrow.resolution, Resolution::Recipient);
+    assert_eq!(token_client.balance(&recipient), 5_000);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_appeal_escalates_to_secondary_arbiter() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let secondary = Address::generate(&env);
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let escrow_id = 431u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &5_300);
+
+    client.init(&admin);
+    client.set_appeal_config(&86_400, &300, &secondary);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 5_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &5_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    client.raise_dispute(&escrow_id, &depositor);
+    client.resolve_dispute(&escrow_id, &recipient);
+
+    // Losing depositor posts the appeal bond
+    client.appeal_ruling(&escrow_id, &depositor);
+    assert_eq!(client.get_escrow(&escrow_id).status, EscrowStatus::Appealed);
+    assert_eq!(token_client.balance(&contract_id), 5_300);
+
+    let result = client.try_execute_ruling(&escrow_id);
+    assert_eq!(result, Err(Ok(Error::InvalidEscrowStatus)));
+
+    // Secondary arbiter overturns the ruling; appellant recovers the bond
+    client.resolve_appeal(&escrow_id, &depositor);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Resolved);
+    assert_eq!(escrow.resolution, Resolution::Depositor);
+    assert_eq!(token_client.balance(&depositor), 5_300);
+    assert_eq!(token_client.balance(&recipient), 0);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_auto_release_after_review_period() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(100)); // 1%
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 440u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 4_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Design"),
+            due_date: 5_000,
+            review_period: 3_600,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+        Milestone {
+            amount: 6_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Build"),
+            due_date: 9_000,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &10_000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // Nothing to auto-release before delivery
+    let result = client.try_auto_release_milestone(&escrow_id, &0);
+    assert_eq!(result, Err(Ok(Error::MilestoneNotDelivered)));
+
+    env.ledger().with_mut(|li| li.timestamp = 4_000);
+    let proof = BytesN::from_array(&env, &[7; 32]);
+    client.submit_milestone(&escrow_id, &0, &proof);
+    client.submit_milestone(&escrow_id, &1, &proof);
+    assert_eq!(client.get_milestone(&escrow_id, &0).submitted_at, 4_000);
+
+    let result = client.try_auto_release_milestone(&escrow_id, &0);
+    assert_eq!(result, Err(Ok(Error::ReviewPeriodActive)));
+
+    env.ledger().with_mut(|li| li.timestamp = 4_000 + 3_601);
+
+    // Milestones without a review period never auto-release
+    let result = client.try_auto_release_milestone(&escrow_id, &1);
+    assert_eq!(result, Err(Ok(Error::MilestoneNotDelivered)));
+
+    client.auto_release_milestone(&escrow_id, &0);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(
+        client.get_milestone(&escrow_id, &0).status,
+        MilestoneStatus::Released
+    );
+    assert_eq!(escrow.total_released, 4_000);
+    assert_eq!(token_client.balance(&recipient), 3_960);
+    assert_eq!(token_client.balance(&treasury), 40);
+}
+
+#[test]
+fn test_milestone_due_date_after_deadline_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (_, token_address) = create_test_token(&env, &admin);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Late"),
+            due_date: 2_000,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    let result = client.try_create_escrow(
+        &441u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1_000u64,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidDeadline)));
+}
+
+#[test]
+fn test_submit_reject_and_confirm_milestone() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 450u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &3_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 3_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Report"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &3_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // Only submitted milestones can be rejected
+    let result = client.try_reject_milestone(&escrow_id, &0, &symbol_short!("Early"));
+    assert_eq!(result, Err(Ok(Error::InvalidMilestoneStatus)));
+
+    let draft = BytesN::from_array(&env, &[1; 32]);
+    client.submit_milestone(&escrow_id, &0, &draft);
+    assert_eq!(
+        client.get_milestone(&escrow_id, &0).status,
+        MilestoneStatus::Submitted
+    );
+    assert_eq!(client.get_milestone_proof(&escrow_id, &0), draft);
+
+    // A submitted milestone cannot be submitted again until reviewed
+    let result = client.try_submit_milestone(&escrow_id, &0, &draft);
+    assert_eq!(result, Err(Ok(Error::InvalidMilestoneStatus)));
+
+    client.reject_milestone(&escrow_id, &0, &symbol_short!("Typos"));
+
+    let events = env.events().all();
+    let event = events.last().unwrap();
+    let expected_topics: soroban_sdk::Vec<soroban_sdk::Val> = (
+        Symbol::new(&env, "Vaultix"),
+        Symbol::new(&env, "MilestoneRejected"),
+        EVENT_VERSION,
+        escrow_id,
+    )
+        .into_val(&env);
+    assert_eq!(event.1, expected_topics);
+    let (_, payload): (u64, MilestoneRejectedEvent) = event.2.into_val(&env);
+    assert_eq!(
+        payload,
+        MilestoneRejectedEvent {
+            milestone_index: 0,
+            reason: symbol_short!("Typos"),
+        }
+    );
+
+    let milestone = client.get_milestone(&escrow_id, &0);
+    assert_eq!(milestone.status, MilestoneStatus::Pending);
+    assert_eq!(milestone.submitted_at, 0);
+    assert!(client.try_get_milestone_proof(&escrow_id, &0).is_err());
+
+    let final_version = BytesN::from_array(&env, &[2; 32]);
+    client.submit_milestone(&escrow_id, &0, &final_version);
+    client.confirm_delivery(&escrow_id, &0, &depositor);
+
+    assert_eq!(
+        client.get_milestone(&escrow_id, &0).status,
+        MilestoneStatus::Released
+    );
+    assert_eq!(token_client.balance(&recipient), 3_000);
+}
+
+#[test]
+fn test_claim_vested_accrues_linearly_and_freezes_in_dispute() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 460u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Retainer"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Vesting(1_000, 2_000),
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &10_000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    env.ledger().with_mut(|li| li.timestamp = 900);
+    let result = client.try_claim_vested(&escrow_id, &0);
+    assert_eq!(result, Err(Ok(Error::NothingToClaim)));
+
+    // A quarter of the way through
+    env.ledger().with_mut(|li| li.timestamp = 1_250);
+    client.claim_vested(&escrow_id, &0);
+    assert_eq!(token_client.balance(&recipient), 2_500);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.total_released, 2_500);
+    assert_eq!(client.get_milestone(&escrow_id, &0).claimed, 2_500);
+
+    // Nothing more to claim at the same timestamp
+    let result = client.try_claim_vested(&escrow_id, &0);
+    assert_eq!(result, Err(Ok(Error::NothingToClaim)));
+
+    // Claims are blocked while disputed and the dispute time does not accrue
+    env.ledger().with_mut(|li| li.timestamp = 1_500);
+    client.raise_dispute(&escrow_id, &depositor);
+    env.ledger().with_mut(|li| li.timestamp = 1_800);
+    let result = client.try_claim_vested(&escrow_id, &0);
+    assert_eq!(result, Err(Ok(Error::EscrowNotActive)));
+    client.withdraw_dispute(&escrow_id, &depositor);
+
+    let milestone = client.get_milestone(&escrow_id, &0);
+    assert_eq!(milestone.kind, MilestoneKind::Vesting(1_300, 2_300));
+
+    // Vested at 1_800 equals what had vested when the dispute was raised
+    client.claim_vested(&escrow_id, &0);
+    assert_eq!(token_client.balance(&recipient), 5_000);
+
+    // Fully vested milestone is marked released
+    env.ledger().with_mut(|li| li.timestamp = 2_300);
+    client.claim_vested(&escrow_id, &0);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(
+        client.get_milestone(&escrow_id, &0).status,
+        MilestoneStatus::Released
+    );
+    assert_eq!(escrow.total_released, 10_000);
+    assert_eq!(token_client.balance(&recipient), 10_000);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_cancel_mid_vesting_pays_accrued_share() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 461u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Retainer"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Vesting(1_000, 2_000),
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &10_000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // 40% has vested but was never claimed
+    env.ledger().with_mut(|li| li.timestamp = 1_400);
+    client.cancel_escrow(&escrow_id);
+
+    assert_eq!(token_client.balance(&recipient), 4_000);
+    assert_eq!(token_client.balance(&depositor), 6_000);
+    assert_eq!(token_client.balance(&contract_id), 0);
+
+    let milestone = client.get_milestone(&escrow_id, &0);
+    assert_eq!(milestone.status, MilestoneStatus::Cancelled);
+    assert_eq!(milestone.claimed, 4_000);
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Cancelled);
+    assert_eq!(escrow.total_released, 4_000);
+    assert_eq!(escrow.total_refunded, 6_000);
+}
+
+#[test]
+fn test_subscription_schedule_with_cliff_and_cancellation() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 470u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &4_000);
+
+    let schedule = PaymentSchedule {
+        period_amount: 1_000,
+        periods: 4,
+        start: 0,
+        interval: 100,
+        cliff: 250,
+        notice: 50,
+    };
+    client.create_subscription(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &schedule,
+        &1_000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &4_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // Periods inside the cliff unlock together when it ends
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.total_amount, 4_000);
+    let expected_unlocks = [250u64, 250, 300, 400];
+    for (i, unlock_at) in expected_unlocks.iter().enumerate() {
+        assert_eq!(
+            client.get_milestone(&escrow_id, &(i as u32)).kind,
+            MilestoneKind::Scheduled(*unlock_at)
+        );
+    }
+
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    let result = client.try_claim_vested(&escrow_id, &0);
+    assert_eq!(result, Err(Ok(Error::NothingToClaim)));
+
+    env.ledger().with_mut(|li| li.timestamp = 260);
+    client.claim_vested(&escrow_id, &0);
+    client.claim_vested(&escrow_id, &1);
+    assert_eq!(token_client.balance(&recipient), 2_000);
+
+    // Period unlocking within the notice window survives the cancellation
+    client.cancel_subscription(&escrow_id);
+    assert_eq!(token_client.balance(&depositor), 1_000);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.total_refunded, 1_000);
+    assert_eq!(
+        client.get_milestone(&escrow_id, &2).status,
+        MilestoneStatus::Pending
+    );
+    assert_eq!(
+        client.get_milestone(&escrow_id, &3).status,
+        MilestoneStatus::Cancelled
+    );
+
+    // A cancelled period was refunded and cannot also be paid out
+    let result = client.try_release_milestone(&escrow_id, &3);
+    assert_eq!(result, Err(Ok(Error::InvalidMilestoneStatus)));
+    let result = client.try_confirm_delivery(&escrow_id, &3, &depositor);
+    assert_eq!(result, Err(Ok(Error::InvalidMilestoneStatus)));
+    assert_eq!(token_client.balance(&recipient), 2_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 300);
+    client.claim_vested(&escrow_id, &2);
+    assert_eq!(token_client.balance(&recipient), 3_000);
+    assert_eq!(token_client.balance(&contract_id), 0);
+
+    client.complete_escrow(&escrow_id);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Completed);
+}
+
+#[test]
+fn test_subscription_cancel_and_expiry_pay_unlocked_periods() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 471u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &3_000);
+
+    let schedule = PaymentSchedule {
+        period_amount: 1_000,
+        periods: 3,
+        start: 0,
+        interval: 100,
+        cliff: 0,
+        notice: 500,
+    };
+    client.create_subscription(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &schedule,
+        &1_000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &3_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // Cancelling outright would skip the notice period
+    env.ledger().with_mut(|li| li.timestamp = 150);
+    let result = client.try_cancel_escrow(&escrow_id);
+    assert_eq!(result, Err(Ok(Error::InvalidEscrowStatus)));
+    assert_eq!(token_client.balance(&contract_id), 3_000);
+
+    // Periods that unlocked before expiry go to the recipient, claimed or not
+    env.ledger().with_mut(|li| li.timestamp = 250);
+    client.claim_vested(&escrow_id, &0);
+    env.ledger().with_mut(|li| li.timestamp = 1_001);
+    client.refund_expired(&escrow_id, &depositor);
+
+    assert_eq!(token_client.balance(&recipient), 3_000);
+    assert_eq!(token_client.balance(&depositor), 0);
+    for index in 0..3 {
+        assert_eq!(
+            client.get_milestone(&escrow_id, &index).status,
+            MilestoneStatus::Released
+        );
+    }
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Expired);
+    assert_eq!(escrow.total_released, 3_000);
+    assert_eq!(escrow.total_refunded, 0);
+}
+
+/// Stand-in for an external delivery verifier (shipping oracle, CI, etc.)
+#[contract]
+pub struct MockVerifier;
+
+#[contractimpl]
+impl MockVerifier {
+    pub fn set_verified(env: Env, verified: bool) {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("verified"), &verified);
+    }
+
+    pub fn verify(env: Env, _escrow_id: u64, _milestone_index: u32) -> bool {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("verified"))
+            .unwrap_or(false)
+    }
+}
+
+#[test]
+fn test_release_if_verified_uses_verifier_contract() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let verifier_id = env.register_contract(None, MockVerifier);
+    let verifier = MockVerifierClient::new(&env, &verifier_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 480u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &3_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 2_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Shipped"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::Verifier(verifier_id.clone()),
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Support"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &3_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // Verifier has not confirmed delivery yet
+    let result = client.try_release_if_verified(&escrow_id, &0);
+    assert_eq!(result, Err(Ok(Error::ConditionNotMet)));
+
+    // Milestones without a verifier cannot be released this way
+    let result = client.try_release_if_verified(&escrow_id, &1);
+    assert_eq!(result, Err(Ok(Error::ConditionNotMet)));
+
+    verifier.set_verified(&true);
+    client.release_if_verified(&escrow_id, &0);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(
+        client.get_milestone(&escrow_id, &0).status,
+        MilestoneStatus::Released
+    );
+    assert_eq!(escrow.total_released, 2_000);
+    assert_eq!(token_client.balance(&recipient), 2_000);
+}
+
+#[test]
+fn test_hashlock_release_and_timelock_refund() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 490u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &3_000);
+
+    let secret = Bytes::from_slice(&env, b"swap-secret");
+    let other_secret = Bytes::from_slice(&env, b"never-revealed");
+    let hash = env.crypto().sha256(&secret);
+    let other_hash = env.crypto().sha256(&other_secret);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 2_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("SwapLeg"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::HashLock(hash, 500),
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Key"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::HashLock(other_hash, 500),
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1_000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &3_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    env.ledger().with_mut(|li| li.timestamp = 100);
+
+    let result = client.try_release_with_preimage(&escrow_id, &0, &other_secret);
+    assert_eq!(result, Err(Ok(Error::InvalidPreimage)));
+
+    let result = client.try_refund_hashlock(&escrow_id, &1);
+    assert_eq!(result, Err(Ok(Error::HashLockActive)));
+
+    client.release_with_preimage(&escrow_id, &0, &secret);
+    assert_eq!(token_client.balance(&recipient), 2_000);
+
+    // After the timelock the remaining leg can only be refunded
+    env.ledger().with_mut(|li| li.timestamp = 501);
+    let result = client.try_release_with_preimage(&escrow_id, &1, &other_secret);
+    assert_eq!(result, Err(Ok(Error::HashLockExpired)));
+
+    client.refund_hashlock(&escrow_id, &1);
+    assert_eq!(token_client.balance(&depositor), 1_000);
+    assert_eq!(token_client.balance(&contract_id), 0);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(
+        client.get_milestone(&escrow_id, &1).status,
+        MilestoneStatus::Refunded
+    );
+    assert_eq!(escrow.total_refunded, 1_000);
+
+    // A refunded leg can no longer be paid out to the recipient
+    let result = client.try_release_milestone(&escrow_id, &1);
+    assert_eq!(result, Err(Ok(Error::InvalidMilestoneStatus)));
+    let result = client.try_confirm_delivery(&escrow_id, &1, &depositor);
+    assert_eq!(result, Err(Ok(Error::InvalidMilestoneStatus)));
+    assert_eq!(token_client.balance(&recipient), 2_000);
+
+    client.complete_escrow(&escrow_id);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Completed);
+}
+
+#[test]
+fn test_cancel_waits_for_open_hashlocks() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 491u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &3_000);
+
+    let hash = env
+        .crypto()
+        .sha256(&Bytes::from_slice(&env, b"swap-secret"));
+    let leg = |amount: i128, timelock: u64| Milestone {
+        amount,
+        status: MilestoneStatus::Pending,
+        description: symbol_short!("SwapLeg"),
+        due_date: 0,
+        review_period: 0,
+        submitted_at: 0,
+        kind: MilestoneKind::Fixed,
+        claimed: 0,
+        condition: ReleaseCondition::HashLock(hash.clone(), timelock),
+        payees: vec![&env],
+        approval: ApprovalRule::Depositor,
+        metadata: MilestoneMetadata::empty(&env),
+    };
+    let milestones = vec![&env, leg(1_000, 500), leg(2_000, 300)];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1_000u64,
+    );
+    assert_eq!(client.get_escrow(&escrow_id).hashlock_until, 500);
+    token_client.approve(&depositor, &contract_id, &3_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // The recipient may hold the preimage, so the depositor cannot pull the
+    // funds back before the timelock
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    let result = client.try_cancel_escrow(&escrow_id);
+    assert_eq!(result, Err(Ok(Error::HashLockActive)));
+
+    // One leg past its timelock is not enough while the other is open
+    env.ledger().with_mut(|li| li.timestamp = 400);
+    let result = client.try_cancel_escrow(&escrow_id);
+    assert_eq!(result, Err(Ok(Error::HashLockActive)));
+    client.refund_hashlock(&escrow_id, &1);
+    assert_eq!(token_client.balance(&depositor), 2_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 501);
+    client.cancel_escrow(&escrow_id);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Cancelled);
+    assert_eq!(token_client.balance(&depositor), 3_000);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_multisig_depositor_release_and_dispute() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 491u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &3_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase1"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+        Milestone {
+            amount: 2_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Phase2"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1_000u64,
+    );
+
+    let signer_a = Address::generate(&env);
+    let signer_b = Address::generate(&env);
+    let signer_c = Address::generate(&env);
+    let signers = vec![&env, signer_a.clone(), signer_b.clone(), signer_c.clone()];
+
+    // Threshold above the signer count is rejected
+    let result = client.try_set_party_signers(&escrow_id, &Party::Depositor, &signers, &4);
+    assert_eq!(result, Err(Ok(Error::InvalidSignerSet)));
+
+    client.set_party_signers(&escrow_id, &Party::Depositor, &signers, &2);
+    let set = client
+        .get_party_signers(&escrow_id, &Party::Depositor)
+        .unwrap();
+    assert_eq!(set.threshold, 2);
+
+    token_client.approve(&depositor, &contract_id, &3_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // The depositor key alone can no longer release
+    let result = client.try_release_milestone(&escrow_id, &0);
+    assert_eq!(result, Err(Ok(Error::MultisigRequired)));
+
+    // Outsiders cannot approve
+    let outsider = Address::generate(&env);
+    let action = PartyAction::ReleaseMilestone(0);
+    let result = client.try_approve_action(&escrow_id, &outsider, &Party::Depositor, &action);
+    assert_eq!(result, Err(Ok(Error::UnauthorizedAccess)));
+
+    client.approve_action(&escrow_id, &signer_a, &Party::Depositor, &action);
+    client.approve_action(&escrow_id, &signer_a, &Party::Depositor, &action);
+    assert_eq!(
+        client.get_pending_approvals(&escrow_id, &Party::Depositor, &action),
+        vec![&env, signer_a.clone()]
+    );
+    assert_eq!(token_client.balance(&recipient), 0);
+
+    client.approve_action(&escrow_id, &signer_c, &Party::Depositor, &action);
+    assert_eq!(token_client.balance(&recipient), 1_000);
+    assert!(client
+        .get_pending_approvals(&escrow_id, &Party::Depositor, &action)
+        .is_empty());
+
+    // A single-key recipient still disputes directly; a multisig depositor must approve
+    let result = client.try_raise_dispute(&escrow_id, &depositor);
+    assert_eq!(result, Err(Ok(Error::MultisigRequired)));
+
+    let dispute = PartyAction::RaiseDispute;
+    client.approve_action(&escrow_id, &signer_b, &Party::Depositor, &dispute);
+    client.approve_action(&escrow_id, &signer_c, &Party::Depositor, &dispute);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Disputed);
+    assert_eq!(client.get_dispute(&escrow_id).raised_by, depositor);
+
+    // Withdrawing and settling also need the depositor's threshold
+    let result = client.try_withdraw_dispute(&escrow_id, &depositor);
+    assert_eq!(result, Err(Ok(Error::MultisigRequired)));
+    let withdraw = PartyAction::WithdrawDispute;
+    client.approve_action(&escrow_id, &signer_a, &Party::Depositor, &withdraw);
+    client.approve_action(&escrow_id, &signer_b, &Party::Depositor, &withdraw);
+    assert_eq!(client.get_escrow(&escrow_id).status, EscrowStatus::Active);
+
+    client.raise_dispute(&escrow_id, &recipient);
+    client.propose_settlement(&escrow_id, &recipient, &1_500);
+    let result = client.try_propose_settlement(&escrow_id, &depositor, &1_000);
+    assert_eq!(result, Err(Ok(Error::MultisigRequired)));
+    let result = client.try_accept_settlement(&escrow_id, &depositor, &1_500);
+    assert_eq!(result, Err(Ok(Error::MultisigRequired)));
+
+    let accept = PartyAction::AcceptSettlement(1_500);
+    client.approve_action(&escrow_id, &signer_a, &Party::Depositor, &accept);
+    assert_eq!(client.get_escrow(&escrow_id).status, EscrowStatus::Disputed);
+    client.approve_action(&escrow_id, &signer_c, &Party::Depositor, &accept);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Resolved);
+    assert_eq!(escrow.resolution, Resolution::Settled);
+    assert_eq!(token_client.balance(&recipient), 2_500);
+    assert_eq!(token_client.balance(&depositor), 500);
+}
+
+#[test]
+fn test_multisig_depositor_rejects_appeals_and_refunds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+    let admin = Address::generate(&env);
+    let secondary = Address::generate(&env);
+    client.init(&admin);
+    client.set_appeal_config(&86_400, &100, &secondary);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let signer_a = Address::generate(&env);
+    let signer_b = Address::generate(&env);
+    let signers = vec![&env, signer_a.clone(), signer_b.clone()];
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &3_000);
+    token_admin.mint(&signer_b, &100);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Work"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+    client.create_escrow(
+        &493u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &10_000u64,
+    );
+    client.set_party_signers(&493, &Party::Depositor, &signers, &2);
+    token_client.approve(&depositor, &contract_id, &1_000, &200);
+    client.deposit_funds(&493);
+
+    // Sending work back needs the threshold, and the signers agree on the reason
+    client.submit_milestone(&493, &0, &BytesN::from_array(&env, &[1; 32]));
+    let result = client.try_reject_milestone(&493, &0, &symbol_short!("Redo"));
+    assert_eq!(result, Err(Ok(Error::MultisigRequired)));
+    let reject = PartyAction::RejectMilestone(0, symbol_short!("Redo"));
+    for signer in [&signer_a, &signer_b] {
+        client.approve_action(&493, signer, &Party::Depositor, &reject);
+    }
+    assert_eq!(
+        client.get_milestone(&493, &0).status,
+        MilestoneStatus::Pending
+    );
+
+    // So does appealing a ruling; the signer completing the approval posts the bond
+    client.raise_dispute(&493, &recipient);
+    client.resolve_dispute(&493, &recipient);
+    let result = client.try_appeal_ruling(&493, &depositor);
+    assert_eq!(result, Err(Ok(Error::MultisigRequired)));
+    for signer in [&signer_a, &signer_b] {
+        client.approve_action(&493, signer, &Party::Depositor, &PartyAction::AppealRuling);
+    }
+    assert_eq!(client.get_escrow(&493).status, EscrowStatus::Appealed);
+    assert_eq!(token_client.balance(&signer_b), 0);
+    assert_eq!(client.get_dispute(&493).appeal_bond, 100);
+
+    let schedule = PaymentSchedule {
+        period_amount: 1_000,
+        periods: 2,
+        start: 0,
+        interval: 100,
+        cliff: 0,
+        notice: 100,
+    };
+    client.create_subscription(
+        &494u64,
+        &depositor,
+        &recipient,
+        &token_address,
+        &schedule,
+        &1_000u64,
+    );
+    client.set_party_signers(&494, &Party::Depositor, &signers, &2);
+    token_client.approve(&depositor, &contract_id, &2_000, &200);
+    client.deposit_funds(&494);
+
+    // Stopping a subscription and reclaiming an expired escrow too
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    let result = client.try_cancel_subscription(&494);
+    assert_eq!(result, Err(Ok(Error::MultisigRequired)));
+    for signer in [&signer_a, &signer_b] {
+        client.approve_action(
+            &494,
+            signer,
+            &Party::Depositor,
+            &PartyAction::CancelSubscription,
+        );
+    }
+    assert_eq!(token_client.balance(&depositor), 1_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_001);
+    let result = client.try_refund_expired(&494, &depositor);
+    assert_eq!(result, Err(Ok(Error::MultisigRequired)));
+    let result = client.try_approve_action(
+        &494,
+        &signer_a,
+        &Party::Recipient,
+        &PartyAction::RefundExpired,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidSignerSet)));
+    for signer in [&signer_a, &signer_b] {
+        client.approve_action(&494, signer, &Party::Depositor, &PartyAction::RefundExpired);
+    }
+    assert_eq!(client.get_state(&494), EscrowStatus::Expired);
+    assert_eq!(token_client.balance(&recipient), 1_000);
+}
+
+#[test]
+fn test_milestone_payees_split_releases_and_rulings() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(50));
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let designer = Address::generate(&env);
+    let developer = Address::generate(&env);
+    let tester = Address::generate(&env);
+    let escrow_id = 492u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &3_000);
+
+    let mut milestones = vec![
+        &env,
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Design"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![
+                &env,
+                Payee {
+                    address: designer.clone(),
+                    bps: 6_000,
+                },
+                Payee {
+                    address: developer.clone(),
+                    bps: 3_000,
+                },
+            ],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+        Milestone {
+            amount: 2_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Build"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![
+                &env,
+                Payee {
+                    address: developer.clone(),
+                    bps: 6_667,
+                },
+                Payee {
+                    address: tester.clone(),
+                    bps: 3_333,
+                },
+            ],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    // Splits must cover exactly 100%
+    let result = client.try_create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1_000u64,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidMilestoneAmount)));
+
+    let mut design = milestones.get(0).unwrap();
+    design.payees.set(
+        1,
+        Payee {
+            address: developer.clone(),
+            bps: 4_000,
+        },
+    );
+    milestones.set(0, design);
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1_000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &3_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // Fee taken once on 1000, then 995 split 60/40
+    client.release_milestone(&escrow_id, &0);
+    assert_eq!(token_client.balance(&treasury), 5);
+    assert_eq!(token_client.balance(&designer), 597);
+    assert_eq!(token_client.balance(&developer), 398);
+
+    // A payee is only owed part of some milestones, so cannot freeze the
+    // whole escrow; the recipient disputes on their behalf
+    client.set_dispute_bond(&token_address, &50);
+    token_admin.mint(&tester, &50);
+    token_admin.mint(&recipient, &50);
+    let result = client.try_raise_dispute(&escrow_id, &tester);
+    assert_eq!(result, Err(Ok(Error::UnauthorizedAccess)));
+    assert_eq!(token_client.balance(&tester), 50);
+
+    // A recipient ruling pays the open milestone's payees
+    client.raise_dispute(&escrow_id, &recipient);
+    client.resolve_dispute(&escrow_id, &recipient);
+
+    assert_eq!(token_client.balance(&developer), 398 + 1_333);
+    assert_eq!(token_client.balance(&tester), 667 + 50);
+    assert_eq!(token_client.balance(&recipient), 50);
+    assert_eq!(token_client.balance(&contract_id), 0);
+
+    let outsider = Address::generate(&env);
+    let result = client.try_raise_dispute(&escrow_id, &outsider);
+    assert_eq!(result, Err(Ok(Error::UnauthorizedAccess)));
+}
+
+#[test]
+fn test_milestone_approver_rules() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let inspector = Address::generate(&env);
+    let agent = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 493u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &4_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Inspect"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::DepositorAndApprover(inspector.clone()),
+            metadata: MilestoneMetadata::empty(&env),
+        },
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Agent"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::DepositorOrApprover(agent.clone()),
+            metadata: MilestoneMetadata::empty(&env),
+        },
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Final"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Review"),
+            due_date: 0,
+            review_period: 100,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::DepositorAndApprover(inspector.clone()),
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1_000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &4_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // The inspector cannot release alone
+    let result = client.try_release_by_approver(&escrow_id, &0);
+    assert_eq!(result, Err(Ok(Error::UnauthorizedAccess)));
+
+    // Depositor release also needs the inspector's signature
+    client.release_milestone(&escrow_id, &0);
+    let auths = env.auths();
+    assert!(auths.iter().any(|(addr, _)| *addr == depositor));
+    assert!(auths.iter().any(|(addr, _)| *addr == inspector));
+
+    // The agent can release on its own
+    client.release_by_approver(&escrow_id, &1);
+    let auths = env.auths();
+    assert!(auths.iter().any(|(addr, _)| *addr == agent));
+    assert!(!auths.iter().any(|(addr, _)| *addr == depositor));
+    assert_eq!(token_client.balance(&recipient), 2_000);
+
+    // A lapsed review period replaces the depositor, not the inspector
+    env.ledger().with_mut(|li| li.timestamp = 500);
+    client.submit_milestone(&escrow_id, &3, &BytesN::from_array(&env, &[3; 32]));
+    env.ledger().with_mut(|li| li.timestamp = 601);
+    client.auto_release_milestone(&escrow_id, &3);
+    let auths = env.auths();
+    assert!(auths.iter().any(|(addr, _)| *addr == inspector));
+    assert_eq!(token_client.balance(&recipient), 3_000);
+
+    // Approvers can raise disputes
+    client.init(&admin);
+    client.set_dispute_bond(&token_address, &40);
+    token_admin.mint(&inspector, &40);
+    client.raise_dispute(&escrow_id, &inspector);
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.status, EscrowStatus::Disputed);
+    assert_eq!(client.get_dispute(&escrow_id).raised_by, inspector);
+
+    // The inspector sits on the depositor's side, so withdrawing forfeits the
+    // bond to the recipient
+    client.withdraw_dispute(&escrow_id, &inspector);
+    assert_eq!(token_client.balance(&inspector), 0);
+    assert_eq!(token_client.balance(&depositor), 0);
+    assert_eq!(token_client.balance(&recipient), 3_040);
+    assert_eq!(client.get_escrow(&escrow_id).status, EscrowStatus::Active);
+}
+
+#[test]
+fn test_claim_vested_needs_and_approver() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let inspector = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 495u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Retainer"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Vesting(0, 100),
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::DepositorAndApprover(inspector.clone()),
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1_000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &1_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    client.claim_vested(&escrow_id, &0);
+    let auths = env.auths();
+    assert!(auths.iter().any(|(addr, _)| *addr == recipient));
+    assert!(auths.iter().any(|(addr, _)| *addr == inspector));
+    assert_eq!(token_client.balance(&recipient), 500);
+}
+
+#[test]
+fn test_assign_recipient_with_depositor_consent() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let factor = Address::generate(&env);
+    let escrow_id = 494u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &2_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 2_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Invoice"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1_000u64,
+    );
+    client.set_assignment_consent(&escrow_id, &true);
+    let event = env.events().all().last().unwrap();
+    let (_, consent): (u64, AssignmentConsentSetEvent) = event.2.into_val(&env);
+    assert!(consent.required);
+    token_client.approve(&depositor, &contract_id, &2_000, &200);
+    client.deposit_funds(&escrow_id);
+    client.raise_dispute(&escrow_id, &recipient);
+
+    let result = client.try_assign_recipient(&escrow_id, &depositor);
+    assert_eq!(result, Err(Ok(Error::SelfDealing)));
+
+    client.assign_recipient(&escrow_id, &factor);
+    let auths = env.auths();
+    assert!(auths.iter().any(|(addr, _)| *addr == recipient));
+    assert!(auths.iter().any(|(addr, _)| *addr == depositor));
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.recipient, factor);
+    assert_eq!(escrow.status, EscrowStatus::Disputed);
+    assert_eq!(client.get_dispute(&escrow_id).raised_by, factor);
+
+    assert!(client.get_escrows_by_party(&recipient, &0).is_empty());
+    assert_eq!(
+        client.get_escrows_by_party(&factor, &0),
+        vec![&env, escrow_id]
+    );
+    assert_eq!(
+        client.get_escrows_by_party(&depositor, &0),
+        vec![&env, escrow_id]
+    );
+
+    // The payout follows the position
+    client.resolve_dispute(&escrow_id, &factor);
+    assert_eq!(token_client.balance(&factor), 2_000);
+    assert_eq!(token_client.balance(&recipient), 0);
+}
+
+#[test]
+fn test_party_index_is_paged() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let factor = Address::generate(&env);
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 100,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    // Anyone can name an address as recipient, so its index must not grow
+    // into a single entry
+    for escrow_id in 0u64..51 {
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+        );
+    }
+    assert_eq!(client.get_party_index_pages(&recipient), 2);
+    assert_eq!(client.get_escrows_by_party(&recipient, &0).len(), 50);
+    assert_eq!(client.get_escrows_by_party(&recipient, &1), vec![&env, 50]);
+    assert!(client.get_escrows_by_party(&recipient, &2).is_empty());
+
+    // Assigning away only touches the page holding the escrow
+    client.assign_recipient(&3, &factor);
+    let first = client.get_escrows_by_party(&recipient, &0);
+    assert_eq!(first.len(), 49);
+    assert!(!first.contains(3));
+    assert_eq!(client.get_escrows_by_party(&factor, &0), vec![&env, 3]);
+    assert_eq!(client.get_party_index_pages(&factor), 1);
+}
+
+#[test]
+fn test_multisig_recipient_assigns_and_drops_signer_set() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let factor = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 508u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &2_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 2_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Invoice"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1_000u64,
+    );
+    let signer_a = Address::generate(&env);
+    let signer_b = Address::generate(&env);
+    let signers = vec![&env, signer_a.clone(), signer_b.clone()];
+    client.set_party_signers(&escrow_id, &Party::Recipient, &signers, &2);
+    token_client.approve(&depositor, &contract_id, &2_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    let dispute = PartyAction::RaiseDispute;
+    client.approve_action(&escrow_id, &signer_a, &Party::Recipient, &dispute);
+
+    let result = client.try_assign_recipient(&escrow_id, &factor);
+    assert_eq!(result, Err(Ok(Error::MultisigRequired)));
+
+    let assign = PartyAction::AssignRecipient(factor.clone());
+    client.approve_action(&escrow_id, &signer_a, &Party::Recipient, &assign);
+    assert_eq!(client.get_escrow(&escrow_id).recipient, recipient);
+    client.approve_action(&escrow_id, &signer_b, &Party::Recipient, &assign);
+
+    assert_eq!(client.get_escrow(&escrow_id).recipient, factor);
+    assert!(client
+        .get_party_signers(&escrow_id, &Party::Recipient)
+        .is_none());
+    assert!(client
+        .get_pending_approvals(&escrow_id, &Party::Recipient, &dispute)
+        .is_empty());
+
+    // The old signers are gone and the new recipient acts with their own key
+    let result = client.try_approve_action(&escrow_id, &signer_b, &Party::Recipient, &dispute);
+    assert_eq!(result, Err(Ok(Error::InvalidSignerSet)));
+    client.raise_dispute(&escrow_id, &factor);
+    assert_eq!(client.get_dispute(&escrow_id).raised_by, factor);
+}
+
+#[test]
+fn test_escrow_request_accept_reject_and_expiry() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Logo"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    let deadline = 2_000_000u64;
+    let first = client.request_escrow(
+        &recipient,
+        &depositor,
+        &token_address,
+        &milestones,
+        &deadline,
+    );
+    let second = client.request_escrow(
+        &recipient,
+        &depositor,
+        &token_address,
+        &milestones,
+        &deadline,
+    );
+    let third = client.request_escrow(
+        &recipient,
+        &depositor,
+        &token_address,
+        &milestones,
+        &deadline,
+    );
+    assert_eq!(
+        client.get_requests_by_party(&depositor, &0),
+        vec![&env, first, second, third]
+    );
+
+    // Accepting opens and funds the escrow in one call
+    token_client.approve(&depositor, &contract_id, &1_000, &200);
+    client.accept_escrow_request(&first, &495u64);
+    let escrow = client.get_escrow(&495u64);
+    assert_eq!(escrow.status, EscrowStatus::Active);
+    assert_eq!(escrow.recipient, recipient);
+    assert_eq!(token_client.balance(&contract_id), 1_000);
+
+    client.reject_escrow_request(&second);
+    let event = env.events().all().last().unwrap();
+    let expected_topics: soroban_sdk::Vec<Val> = (
+        Symbol::new(&env, "Vaultix"),
+        Symbol::new(&env, "EscrowRequestRejected"),
+        EVENT_VERSION,
+    )
+        .into_val(&env);
+    assert_eq!(event.1, expected_topics);
+    let payload: EscrowRequestRejectedEvent = event.2.into_val(&env);
+    assert_eq!(
+        payload,
+        EscrowRequestRejectedEvent {
+            request_id: second,
+            depositor: depositor.clone(),
+        }
+    );
+    assert!(client.try_get_escrow_request(&second).is_err());
+    assert_eq!(
+        client.get_requests_by_party(&recipient, &0),
+        vec![&env, third]
+    );
+
+    // Requests lapse after the validity window
+    let expires_at = client.get_escrow_request(&third).expires_at;
+    env.ledger().with_mut(|li| li.timestamp = expires_at);
+    let result = client.try_accept_escrow_request(&third, &496u64);
+    assert_eq!(result, Err(Ok(Error::InvalidDeadline)));
+}
+
+#[test]
+fn test_escrow_requests_are_capped_per_sender() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let other = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+    let milestones = work_milestones(&env);
+    let deadline = 2_000_000u64;
+
+    for _ in 0..20 {
+        client.request_escrow(
+            &recipient,
+            &depositor,
+            &token_address,
+            &milestones,
+            &deadline,
+        );
+    }
+
+    // A sender at the cap is refused, but cannot crowd out anyone else
+    let result = client.try_request_escrow(
+        &recipient,
+        &depositor,
+        &token_address,
+        &milestones,
+        &deadline,
+    );
+    assert_eq!(result, Err(Ok(Error::VectorTooLarge)));
+    client.request_escrow(&other, &depositor, &token_address, &milestones, &deadline);
+    assert_eq!(client.get_requests_by_party(&depositor, &0).len(), 21);
+
+    // The recipient can withdraw their own request to make room
+    let first = client.get_requests_by_party(&recipient, &0).get(0).unwrap();
+    client.cancel_escrow_request(&first);
+    assert!(env.auths().iter().any(|(addr, _)| *addr == recipient));
+    assert!(client.try_get_escrow_request(&first).is_err());
+    assert_eq!(client.get_requests_by_party(&depositor, &0).len(), 20);
+    client.request_escrow(
+        &recipient,
+        &depositor,
+        &token_address,
+        &milestones,
+        &deadline,
+    );
+
+    // Lapsed requests are cleared to make room
+    env.ledger()
+        .with_mut(|li| li.timestamp = REQUEST_VALIDITY + 1);
+    client.request_escrow(
+        &recipient,
+        &depositor,
+        &token_address,
+        &milestones,
+        &deadline,
+    );
+    assert_eq!(client.get_requests_by_party(&recipient, &0).len(), 1);
+    assert_eq!(client.get_requests_by_party(&depositor, &0).len(), 2);
+    assert_eq!(client.get_request_index_pages(&depositor), 1);
+}
+
+#[test]
+fn test_create_escrow_from_template_with_default_arbiter() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let owner = Address::generate(&env);
+    let arbiter = Address::generate(&env);
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let escrow_id = 497u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &1_001);
+
+    let layout = vec![
+        &env,
+        TemplateMilestone {
+            description: symbol_short!("Design"),
+            bps: 3_000,
+        },
+        TemplateMilestone {
+            description: symbol_short!("Build"),
+            bps: 4_000,
+        },
+        TemplateMilestone {
+            description: symbol_short!("Launch"),
+            bps: 2_000,
+        },
+    ];
+    let result = client.try_register_template(&owner, &layout, &86_400, &Some(arbiter.clone()));
+    assert_eq!(result, Err(Ok(Error::InvalidMilestoneAmount)));
+
+    let mut layout = layout;
+    layout.set(
+        2,
+        TemplateMilestone {
+            description: symbol_short!("Launch"),
+            bps: 3_000,
+        },
+    );
+    let template_id = client.register_template(&owner, &layout, &86_400, &Some(arbiter.clone()));
+    assert_eq!(client.get_template(&template_id).owner, owner);
+
+    assert_eq!(
+        client.get_template_arbiter(&template_id),
+        Some(arbiter.clone())
+    );
+
+    // A party cannot be the arbiter of its own escrow
+    let result = client.try_create_escrow_from_template(
+        &template_id,
+        &escrow_id,
+        &arbiter,
+        &recipient,
+        &token_address,
+        &1_001,
+    );
+    assert_eq!(result, Err(Ok(Error::SelfDealing)));
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    client.create_escrow_from_template(
+        &template_id,
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &1_001,
+    );
+
+    let events = env.events().all();
+    let (_, applied): (u64, TemplateAppliedEvent) =
+        events.get(events.len() - 2).unwrap().2.into_val(&env);
+    assert_eq!(applied.template_id, template_id);
+    let (_, assigned): (u64, ArbiterAssignedEvent) = events.last().unwrap().2.into_val(&env);
+    assert_eq!(assigned.arbiter, arbiter);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.template_id, template_id);
+    assert_eq!(client.get_escrow_arbiter(&escrow_id), Some(arbiter.clone()));
+    assert_eq!(escrow.deadline, 87_400);
+    assert_eq!(escrow.total_amount, 1_001);
+    assert_eq!(client.get_milestone(&escrow_id, &0).amount, 300);
+    assert_eq!(client.get_milestone(&escrow_id, &1).amount, 400);
+    assert_eq!(client.get_milestone(&escrow_id, &2).amount, 301);
+    assert_eq!(
+        client.get_milestone(&escrow_id, &2).description,
+        symbol_short!("Launch")
+    );
+
+    // Disputes go to the template's arbiter rather than the admin
+    token_client.approve(&depositor, &contract_id, &1_001, &200);
+    client.deposit_funds(&escrow_id);
+    client.raise_dispute(&escrow_id, &recipient);
+
+    let result = client.try_resolve_dispute(&escrow_id, &recipient);
+    assert_eq!(result, Err(Ok(Error::PanelRequired)));
+
+    client.cast_arbiter_vote(&escrow_id, &arbiter, &Ruling::Recipient);
+    assert_eq!(client.get_escrow(&escrow_id).status, EscrowStatus::Resolved);
+    assert_eq!(token_client.balance(&recipient), 1_001);
+}
+
+#[test]
+fn test_milestone_metadata_stored_and_bounded() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 498u64;
+
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+
+    let metadata = MilestoneMetadata {
+        title: String::from_str(&env, "Responsive landing page with signup flow"),
+        spec_hash: env
+            .crypto()
+            .sha256(&Bytes::from_slice(&env, b"landing page spec v2")),
+        uri: String::from_str(
+            &env,
+            "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+        ),
+        criteria: vec![&env, 1, 2, 5],
+    };
+    let mut milestones = vec![
+        &env,
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Landing"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: metadata.clone(),
+        },
+    ];
+
+    // Titles are bounded
+    let mut oversized = milestones.get(0).unwrap();
+    oversized.metadata.title = String::from_str(
+        &env,
+        "An extremely long milestone title that keeps going well past the limit the \
+         contract allows for titles, because real specs belong in the linked document",
+    );
+    milestones.set(0, oversized);
+    let result = client.try_create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1_000u64,
+    );
+    assert_eq!(result, Err(Ok(Error::VectorTooLarge)));
+
+    let mut milestone = milestones.get(0).unwrap();
+    milestone.metadata = metadata.clone();
+    milestones.set(0, milestone);
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1_000u64,
+    );
+
+    assert_eq!(client.get_milestone(&escrow_id, &0).metadata, metadata);
+
+    let event = env.events().all().last().unwrap();
+    let (_, payload): (u64, EscrowCreatedEvent) = event.2.into_val(&env);
+    assert_eq!(payload.milestones, vec![&env, metadata]);
+}
+
+#[test]
+fn test_escrow_metadata_and_external_ref_lookup() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 499u64;
+
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Order"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    let mut entries = Map::new(&env);
+    entries.set(symbol_short!("channel"), String::from_str(&env, "web"));
+    let metadata = EscrowMetadata {
+        external_ref: String::from_str(&env, "order-1001"),
+        tags: vec![&env, symbol_short!("retail"), symbol_short!("priority")],
+        entries,
+    };
+
+    client.create_escrow_with_metadata(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1_000u64,
+        &metadata,
+    );
+    assert_eq!(
+        client.get_escrow_metadata(&escrow_id),
+        Some(metadata.clone())
+    );
+    let event = env.events().all().last().unwrap();
+    let (_, stored): (u64, MetadataUpdatedEvent) = event.2.into_val(&env);
+    assert_eq!(stored.caller, depositor);
+    assert_eq!(stored.external_ref, metadata.external_ref);
+    assert_eq!(
+        client.find_by_external_ref(&depositor, &String::from_str(&env, "order-1001")),
+        vec![&env, escrow_id]
+    );
+
+    let outsider = Address::generate(&env);
+    let result = client.try_update_escrow_metadata(&escrow_id, &outsider, &metadata);
+    assert_eq!(result, Err(Ok(Error::UnauthorizedAccess)));
+
+    // The recipient re-keys the escrow to a new order id
+    let mut updated = metadata.clone();
+    updated.external_ref = String::from_str(&env, "order-1002");
+    client.update_escrow_metadata(&escrow_id, &recipient, &updated);
+
+    let event = env.events().all().last().unwrap();
+    let expected_topics: soroban_sdk::Vec<soroban_sdk::Val> = (
+        Symbol::new(&env, "Vaultix"),
+        Symbol::new(&env, "MetadataUpdated"),
+        EVENT_VERSION,
+        escrow_id,
+    )
+        .into_val(&env);
+    assert_eq!(event.1, expected_topics);
+
+    assert!(client
+        .find_by_external_ref(&depositor, &String::from_str(&env, "order-1001"))
+        .is_empty());
+    assert_eq!(
+        client.find_by_external_ref(&depositor, &String::from_str(&env, "order-1002")),
+        vec![&env, escrow_id]
+    );
+    assert_eq!(
+        client.get_escrow_metadata(&escrow_id),
+        Some(updated.clone())
+    );
+
+    // Someone else reusing the reference does not show up under this depositor
+    client.create_escrow_with_metadata(
+        &509u64,
+        &outsider,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1_000u64,
+        &updated,
+    );
+    assert_eq!(
+        client.find_by_external_ref(&depositor, &String::from_str(&env, "order-1002")),
+        vec![&env, escrow_id]
+    );
+    assert_eq!(
+        client.find_by_external_ref(&outsider, &String::from_str(&env, "order-1002")),
+        vec![&env, 509u64]
+    );
+}
+
+#[test]
+fn test_paged_milestones_beyond_default_limit() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let escrow_id = 500u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &25_000);
+
+    let mut milestones = Vec::new(&env);
+    for _i in 0..250 {
+        milestones.push_back(Milestone {
+            amount: 100,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        });
+    }
+
+    let result = client.try_create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    assert_eq!(result, Err(Ok(Error::VectorTooLarge)));
+
+    let result = client.try_set_milestone_limit(&251);
+    assert_eq!(result, Err(Ok(Error::VectorTooLarge)));
+    client.set_milestone_limit(&250);
+    assert_eq!(client.get_milestone_limit(), 250);
+    let event = env.events().all().last().unwrap();
+    let payload: MilestoneLimitUpdatedEvent = event.2.into_val(&env);
+    assert_eq!(
+        payload,
+        MilestoneLimitUpdatedEvent {
+            old_limit: 20,
+            new_limit: 250,
+        }
+    );
+
+    env.budget().reset_default();
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &25_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.milestone_count, 250);
+    assert_eq!(escrow.open_milestones, 250);
+
+    client.release_milestone(&escrow_id, &33);
+    let escrow = client.get_escrow(&escrow_id);
+    assert_eq!(escrow.open_milestones, 249);
+    assert_eq!(escrow.total_released, 100);
+    assert_eq!(token_client.balance(&recipient), 100);
+
+    // Pages are clamped to the milestone count
+    let page = client.get_milestones(&escrow_id, &30, &10);
+    assert_eq!(page.len(), 10);
+    assert_eq!(page.get(3).unwrap().status, MilestoneStatus::Released);
+    assert_eq!(client.get_milestones(&escrow_id, &245, &10).len(), 5);
+
+    let result = client.try_get_milestone(&escrow_id, &250);
+    assert!(result.is_err());
+
+    // Work over every milestone runs in pages that each fit the default budget
+    env.budget().reset_default();
+    client.raise_dispute(&escrow_id, &depositor);
+    env.budget().reset_default();
+    client.resolve_dispute(&escrow_id, &recipient);
+    let sweep = client.get_milestone_sweep(&escrow_id).unwrap();
+    assert_eq!(sweep.next, 20);
+    assert_eq!(sweep.end, 250);
+    assert_eq!(
+        client.try_archive_escrow(&escrow_id),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+    let mut left = 230;
+    while left > 0 {
+        env.budget().reset_default();
+        let next = client.sweep_milestones(&escrow_id);
+        assert_eq!(next, left - 20.min(left));
+        left = next;
+    }
+    assert_eq!(client.get_milestone_sweep(&escrow_id), None);
+    assert_eq!(client.get_escrow(&escrow_id).open_milestones, 0);
+    assert_eq!(token_client.balance(&recipient), 25_000);
+
+    env.budget().reset_default();
+    client.archive_escrow(&escrow_id);
+    assert_eq!(
+        client
+            .get_escrow_receipt(&escrow_id)
+            .unwrap()
+            .total_released,
+        25_000
+    );
+    while client.sweep_milestones(&escrow_id) > 0 {
+        env.budget().reset_default();
+    }
+    env.as_contract(&contract_id, || {
+        for index in 0..250 {
+            assert!(!env
+                .storage()
+                .persistent()
+                .has(&get_milestone_key(escrow_id, index)));
+        }
+    });
+    assert_eq!(
+        client.try_sweep_milestones(&escrow_id),
+        Err(Ok(Error::InvalidEscrowStatus))
+    );
+}
+
+#[test]
+fn test_close_out_rejects_drifted_open_count() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 100,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    for (escrow_id, open) in [(505u64, 0u32), (506u64, 2u32)] {
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1706400000u64,
+        );
+        env.as_contract(&contract_id, || {
+            let key = get_storage_key(escrow_id);
+            let mut escrow: Escrow = env.storage().persistent().get(&key).unwrap();
+            escrow.open_milestones = open;
+            env.storage().persistent().set(&key, &escrow);
+        });
+
+        let result = client.try_cancel_escrow(&escrow_id);
+        assert_eq!(result, Err(Ok(Error::MilestoneStateInconsistent)));
+    }
+}
+
+fn storage_key(env: &Env, key: Val) -> xdr::ScVal {
+    xdr::ScVal::try_from_val(env, &key).unwrap()
+}
+
+/// Ledger sequence until which a contract storage entry stays live.
+fn live_until(env: &Env, contract: &Address, key: xdr::ScVal) -> u32 {
+    let contract = match xdr::ScVal::try_from_val(env, &contract.to_val()).unwrap() {
+        xdr::ScVal::Address(address) => address,
+        _ => unreachable!(),
+    };
+    env.to_ledger_snapshot()
+        .ledger_entries
+        .iter()
+        .find_map(|(k, (_, live_until))| match k.as_ref() {
+            xdr::LedgerKey::ContractData(data) if data.contract == contract && data.key == key => {
+                *live_until
+            }
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_bump_escrow_extends_ttl() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(0));
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 501u64;
+
+    let (_, _, token_address) = create_token_contract(&env, &admin);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 1_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("LongRun"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1_000u64,
+    );
+
+    let escrow_key = storage_key(&env, (symbol_short!("escrow"), escrow_id).into_val(&env));
+    let milestone_key = storage_key(
+        &env,
+        (symbol_short!("milestone"), escrow_id, 0u32).into_val(&env),
+    );
+    let instance_key = xdr::ScVal::LedgerKeyContractInstance;
+
+    assert_eq!(
+        live_until(&env, &contract_id, escrow_key.clone()),
+        2_000_000
+    );
+    assert_eq!(
+        live_until(&env, &contract_id, milestone_key.clone()),
+        2_000_000
+    );
+    assert_eq!(
+        live_until(&env, &contract_id, instance_key.clone()),
+        2_000_000
+    );
+
+    // Near the end of the TTL, anyone can extend every entry of the escrow
+    env.ledger().with_mut(|li| li.sequence_number = 1_990_000);
+    client.bump_escrow(&escrow_id);
+    assert_eq!(live_until(&env, &contract_id, escrow_key), 3_990_000);
+    assert_eq!(
+        live_until(&env, &contract_id, milestone_key.clone()),
+        2_000_000
+    );
+
+    // Milestones are extended separately, a page at a time
+    client.bump_milestones(&escrow_id, &0, &20);
+    assert_eq!(live_until(&env, &contract_id, milestone_key), 3_990_000);
+    assert_eq!(live_until(&env, &contract_id, instance_key), 3_990_000);
+
+    assert!(client.try_bump_escrow(&999u64).is_err());
+}
+
+#[test]
+fn test_archive_completed_escrow_leaves_receipt() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 502u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task1"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    // Only closed escrows can be archived
+    let result = client.try_archive_escrow(&escrow_id);
+    assert_eq!(result, Err(Ok(Error::InvalidEscrowStatus)));
+
+    client.confirm_delivery(&escrow_id, &0, &depositor);
+    client.complete_escrow(&escrow_id);
+
+    let escrow = client.get_escrow(&escrow_id);
+    let checkpoint = client.get_escrow_checkpoint(&escrow_id);
+    let record = (escrow, checkpoint).to_xdr(&env);
+
+    let receipt = client.archive_escrow(&escrow_id);
+    assert_eq!(receipt.final_status, EscrowStatus::Completed);
+    assert_eq!(receipt.total_amount, 10_000);
+    assert_eq!(receipt.total_released, 10_000);
+    assert_eq!(receipt.total_refunded, 0);
+    assert_eq!(receipt.record_hash, env.crypto().sha256(&record));
+    assert_eq!(client.get_escrow_receipt(&escrow_id), Some(receipt));
+
+    let archived = client.get_escrow(&escrow_id);
+    assert_eq!(archived.status, EscrowStatus::Archived);
+    assert_eq!(archived.total_released, 10_000);
+    assert_eq!(archived.depositor, depositor);
+    assert_eq!(client.get_state(&escrow_id), EscrowStatus::Archived);
+
+    // The full record is gone and the id cannot be reused
+    assert!(client.try_get_milestone(&escrow_id, &0).is_err());
+    assert_eq!(
+        client.try_archive_escrow(&escrow_id),
+        Err(Ok(Error::EscrowNotFound))
+    );
+    let result = client.try_create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    assert_eq!(result, Err(Ok(Error::EscrowAlreadyExists)));
+    assert!(client
+        .get_escrows_by_party(&depositor, &0)
+        .contains(escrow_id));
+    client.bump_escrow(&escrow_id);
+}
+
+#[test]
+fn test_receipt_totals_cover_every_close_path() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let treasury = Address::generate(&env);
+    client.initialize(&treasury, &Some(100));
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &40_000);
+
+    let milestone = Milestone {
+        amount: 5_000,
+        status: MilestoneStatus::Pending,
+        description: symbol_short!("Task"),
+        due_date: 0,
+        review_period: 0,
+        submitted_at: 0,
+        kind: MilestoneKind::Fixed,
+        claimed: 0,
+        condition: ReleaseCondition::None,
+        payees: vec![&env],
+        approval: ApprovalRule::Depositor,
+        metadata: MilestoneMetadata::empty(&env),
+    };
+    let milestones = vec![&env, milestone.clone(), milestone];
+
+    for escrow_id in 510u64..514 {
+        client.create_escrow(
+            &escrow_id,
+            &depositor,
+            &recipient,
+            &token_address,
+            &milestones,
+            &1_000u64,
+        );
+        token_client.approve(&depositor, &contract_id, &10_000, &200);
+        client.deposit_funds(&escrow_id);
+    }
+
+    // The others pay out their first milestone before closing
+    client.cancel_escrow(&510);
+    for escrow_id in 511u64..514 {
+        client.release_milestone(&escrow_id, &0);
+    }
+
+    client.raise_dispute(&511, &recipient);
+    client.resolve_dispute(&511, &depositor);
+
+    client.raise_dispute(&512, &depositor);
+    client.propose_settlement(&512, &depositor, &2_000);
+    client.accept_settlement(&512, &recipient, &2_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 2_000);
+    client.refund_expired(&513, &depositor);
+
+    for (escrow_id, status, released, refunded) in [
+        (510u64, EscrowStatus::Cancelled, 0, 10_000),
+        (511, EscrowStatus::Resolved, 5_000, 5_000),
+        (512, EscrowStatus::Resolved, 7_000, 3_000),
+        (513, EscrowStatus::Expired, 5_000, 5_000),
+    ] {
+        let receipt = client.archive_escrow(&escrow_id);
+        assert_eq!(receipt.final_status, status);
+        assert_eq!(receipt.total_released, released);
+        assert_eq!(receipt.total_refunded, refunded);
+        assert_eq!(
+            receipt.total_released + receipt.total_refunded,
+            receipt.total_amount
+        );
+    }
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_archive_at_milestone_limit_removes_stored_entries() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.init(&admin);
+    client.set_milestone_limit(&40);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let escrow_id = 507u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &4_000);
+
+    let mut milestones = Vec::new(&env);
+    for _i in 0..40 {
+        milestones.push_back(Milestone {
+            amount: 100,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        });
+    }
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+
+    let signer_a = Address::generate(&env);
+    let signer_b = Address::generate(&env);
+    let signers = vec![&env, signer_a.clone(), signer_b.clone()];
+    client.set_party_signers(&escrow_id, &Party::Depositor, &signers, &2);
+    token_client.approve(&depositor, &contract_id, &4_000, &200);
+    client.deposit_funds(&escrow_id);
+
+    env.ledger().with_mut(|li| li.timestamp = 2000);
+    let proof = BytesN::from_array(&env, &[7; 32]);
+    client.submit_milestone(&escrow_id, &1, &proof);
+    client.submit_milestone(&escrow_id, &2, &proof);
+
+    // A release that never reaches its threshold stays pending
+    let release = PartyAction::ReleaseMilestone(0);
+    client.approve_action(&escrow_id, &signer_a, &Party::Depositor, &release);
+    for signer in [&signer_a, &signer_b] {
+        client.approve_action(
+            &escrow_id,
+            signer,
+            &Party::Depositor,
+            &PartyAction::CancelEscrow,
+        );
+    }
+    // The refund is paid out page by page
+    assert_eq!(token_client.balance(&depositor), 2_000);
+    assert_eq!(client.sweep_milestones(&escrow_id), 0);
+    assert_eq!(token_client.balance(&depositor), 4_000);
+    assert_eq!(client.get_milestone_proof(&escrow_id, &1), proof);
+
+    env.budget().reset_default();
+    client.archive_escrow(&escrow_id);
+    assert_eq!(client.sweep_milestones(&escrow_id), 0);
+    assert_eq!(client.get_milestone_sweep(&escrow_id), None);
+
+    env.as_contract(&contract_id, || {
+        let storage = env.storage().persistent();
+        assert!(!storage.has(&get_approvals_key(escrow_id, Party::Depositor, release)));
+        assert!(!storage.has(&get_pending_index_key(escrow_id)));
+        assert!(!storage.has(&get_signers_key(escrow_id, Party::Depositor)));
+        for index in 0..40 {
+            assert!(!storage.has(&get_milestone_key(escrow_id, index)));
+            assert!(!storage.has(&get_proof_key(escrow_id, index)));
+        }
+    });
+    assert_eq!(client.get_escrow(&escrow_id).status, EscrowStatus::Archived);
+}
+
+/// Rebuilds an escrow's hash chain from the events the contract published.
+fn replay_checkpoint(env: &Env, contract_id: &Address) -> EscrowCheckpoint {
+    let mut hash = BytesN::from_array(env, &[0; 32]);
+    let mut sequence = 0u64;
+    for (contract, topics, data) in env.events().all().iter() {
+        if contract != *contract_id {
+            continue;
+        }
+        let mut preimage: Bytes = hash.into();
+        preimage.append(&(topics, data).to_xdr(env));
+        hash = env.crypto().sha256(&preimage);
+        sequence += 1;
+    }
+    EscrowCheckpoint { sequence, hash }
+}
+
+#[test]
+fn test_escrow_checkpoint_replays_from_events() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 503u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestone = Milestone {
+        amount: 5_000,
+        status: MilestoneStatus::Pending,
+        description: symbol_short!("Task"),
+        due_date: 0,
+        review_period: 0,
+        submitted_at: 0,
+        kind: MilestoneKind::Fixed,
+        claimed: 0,
+        condition: ReleaseCondition::None,
+        payees: vec![&env],
+        approval: ApprovalRule::Depositor,
+        metadata: MilestoneMetadata::empty(&env),
+    };
+    let milestones = vec![&env, milestone.clone(), milestone];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    let checkpoint = client.get_escrow_checkpoint(&escrow_id);
+    assert_eq!(checkpoint.sequence, 1);
+
+    assert_eq!(replay_checkpoint(&env, &contract_id), checkpoint);
+
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&escrow_id);
+    assert_eq!(
+        replay_checkpoint(&env, &contract_id),
+        client.get_escrow_checkpoint(&escrow_id)
+    );
+
+    client.confirm_delivery(&escrow_id, &0, &depositor);
+    let checkpoint = client.get_escrow_checkpoint(&escrow_id);
+    assert_eq!(replay_checkpoint(&env, &contract_id), checkpoint);
+    assert_eq!(checkpoint.sequence, 3);
+
+    // Reads do not advance the chain
+    client.get_escrow(&escrow_id);
+    assert_eq!(client.get_escrow_checkpoint(&escrow_id), checkpoint);
+
+    assert_eq!(
+        client.try_get_escrow_checkpoint(&999u64),
+        Err(Ok(Error::EscrowNotFound))
+    );
+}
+
+/// Checks an event's topic name and the exact set of fields in its payload.
+fn assert_event_shape<E: EscrowEvent>(env: &Env, event: E, name: &str, fields: &[&str]) {
+    assert_eq!(E::NAME, name);
+    assert_payload_fields(env, event.into_val(env), name, fields);
+}
+
+fn assert_contract_event_shape<E: ContractEvent>(env: &Env, event: E, name: &str, fields: &[&str]) {
+    assert_eq!(E::NAME, name);
+    assert_payload_fields(env, event.into_val(env), name, fields);
+}
+
+fn assert_payload_fields(env: &Env, payload: Val, name: &str, fields: &[&str]) {
+    let payload: Map<Symbol, Val> = payload.into_val(env);
+    assert_eq!(payload.len(), fields.len() as u32, "{}", name);
+    for field in fields {
+        assert!(
+            payload.contains_key(Symbol::new(env, field)),
+            "{}.{}",
+            name,
+            field
+        );
+    }
+}
+
+#[test]
+fn test_escrow_event_schema_is_pinned() {
+    let env = Env::default();
+    let a = Address::generate(&env);
+    let b = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0; 32]);
+
+    assert_eq!(EVENT_VERSION, 1);
+
+    assert_event_shape(
+        &env,
+        EscrowCreatedEvent {
+            depositor: a.clone(),
+            recipient: b.clone(),
+            token_address: a.clone(),
+            total_amount: 0,
+            deadline: 0,
+            milestones: vec![&env],
+        },
+        "EscrowCreated",
+        &[
+            "depositor",
+            "recipient",
+            "token_address",
+            "total_amount",
+            "deadline",
+            "milestones",
+        ],
+    );
+    assert_event_shape(
+        &env,
+        EscrowFundedEvent { amount: 0 },
+        "EscrowFunded",
+        &["amount"],
+    );
+    assert_event_shape(
+        &env,
+        SubscriptionCreatedEvent {
+            period_amount: 0,
+            periods: 0,
+            interval: 0,
+            cliff: 0,
+        },
+        "SubscriptionCreated",
+        &["period_amount", "periods", "interval", "cliff"],
+    );
+    assert_event_shape(
+        &env,
+        SubscriptionCancelledEvent {
+            cancelled_periods: 0,
+            refund_amount: 0,
+            fee: 0,
+        },
+        "SubscriptionCancelled",
+        &["cancelled_periods", "refund_amount", "fee"],
+    );
+    assert_event_shape(
+        &env,
+        MetadataUpdatedEvent {
+            caller: a.clone(),
+            external_ref: String::from_str(&env, ""),
+        },
+        "MetadataUpdated",
+        &["caller", "external_ref"],
+    );
+    assert_event_shape(
+        &env,
+        TemplateAppliedEvent { template_id: 0 },
+        "TemplateApplied",
+        &["template_id"],
+    );
+    assert_event_shape(
+        &env,
+        ArbiterAssignedEvent { arbiter: a.clone() },
+        "ArbiterAssigned",
+        &["arbiter"],
+    );
+    assert_event_shape(
+        &env,
+        AssignmentConsentSetEvent { required: false },
+        "AssignmentConsentSet",
+        &["required"],
+    );
+    assert_event_shape(
+        &env,
+        EscrowAssignedEvent {
+            previous: a.clone(),
+            new_recipient: b.clone(),
+        },
+        "EscrowAssigned",
+        &["previous", "new_recipient"],
+    );
+    assert_event_shape(
+        &env,
+        SignersConfiguredEvent {
+            party: Party::Depositor,
+            threshold: 0,
+        },
+        "SignersConfigured",
+        &["party", "threshold"],
+    );
+    assert_event_shape(
+        &env,
+        ApprovalRecordedEvent {
+            signer: a.clone(),
+            approvals: 0,
+            threshold: 0,
+        },
+        "ApprovalRecorded",
+        &["signer", "approvals", "threshold"],
+    );
+    assert_event_shape(
+        &env,
+        MilestoneSubmittedEvent {
+            milestone_index: 0,
+            proof_hash: hash.clone(),
+            submitted_at: 0,
+            due_date: 0,
+        },
+        "MilestoneSubmitted",
+        &["milestone_index", "proof_hash", "submitted_at", "due_date"],
+    );
+    assert_event_shape(
+        &env,
+        MilestoneRejectedEvent {
+            milestone_index: 0,
+            reason: symbol_short!("Typos"),
+        },
+        "MilestoneRejected",
+        &["milestone_index", "reason"],
+    );
+    assert_event_shape(
+        &env,
+        MilestoneReleasedEvent {
+            milestone_index: 0,
+            payout: 0,
+            fee: 0,
+        },
+        "MilestoneReleased",
+        &["milestone_index", "payout", "fee"],
+    );
+    assert_event_shape(
+        &env,
+        VestedClaimedEvent {
+            milestone_index: 0,
+            payout: 0,
+            fee: 0,
+            claimed: 0,
+        },
+        "VestedClaimed",
+        &["milestone_index", "payout", "fee", "claimed"],
+    );
+    assert_event_shape(
+        &env,
+        PreimageRevealedEvent {
+            milestone_index: 0,
+            preimage: Bytes::new(&env),
+        },
+        "PreimageRevealed",
+        &["milestone_index", "preimage"],
+    );
+    assert_event_shape(
+        &env,
+        HashLockRefundedEvent {
+            milestone_index: 0,
+            refund_amount: 0,
+            fee: 0,
+        },
+        "HashLockRefunded",
+        &["milestone_index", "refund_amount", "fee"],
+    );
+    assert_event_shape(
+        &env,
+        DisputeRaisedEvent {
+            raised_by: a.clone(),
+        },
+        "DisputeRaised",
+        &["raised_by"],
+    );
+    assert_event_shape(
+        &env,
+        ArbiterVoteCastEvent {
+            arbiter: a.clone(),
+            ruling: Ruling::Abstain,
+        },
+        "ArbiterVoteCast",
+        &["arbiter", "ruling"],
+    );
+    assert_event_shape(
+        &env,
+        ArbiterReplacedEvent {
+            old_arbiter: a.clone(),
+            new_arbiter: b.clone(),
+        },
+        "ArbiterReplaced",
+        &["old_arbiter", "new_arbiter"],
+    );
+    assert_event_shape(
+        &env,
+        RulingRecordedEvent {
+            winner: a.clone(),
+            appeal_deadline: 0,
+        },
+        "RulingRecorded",
+        &["winner", "appeal_deadline"],
+    );
+    assert_event_shape(
+        &env,
+        RulingAppealedEvent {
+            appellant: a.clone(),
+            bond: 0,
+            arbiter: b.clone(),
+        },
+        "RulingAppealed",
+        &["appellant", "bond", "arbiter"],
+    );
+    assert_event_shape(
+        &env,
+        DisputeResolvedEvent {
+            winner: a.clone(),
+            payout: 0,
+            arbiter_fee: 0,
+            bond: 0,
+            bond_recipient: a.clone(),
+        },
+        "DisputeResolved",
+        &["winner", "payout", "arbiter_fee", "bond", "bond_recipient"],
+    );
+    assert_event_shape(
+        &env,
+        SettlementProposedEvent {
+            proposer: a.clone(),
+            recipient_amount: 0,
+        },
+        "SettlementProposed",
+        &["proposer", "recipient_amount"],
+    );
+    assert_event_shape(
+        &env,
+        DisputeSettledEvent {
+            recipient_amount: 0,
+            depositor_amount: 0,
+        },
+        "DisputeSettled",
+        &["recipient_amount", "depositor_amount"],
+    );
+    assert_event_shape(
+        &env,
+        DisputeWithdrawnEvent {
+            withdrawn_by: a.clone(),
+        },
+        "DisputeWithdrawn",
+        &["withdrawn_by"],
+    );
+    assert_event_shape(
+        &env,
+        EscrowCompletedEvent { total_released: 0 },
+        "EscrowCompleted",
+        &["total_released"],
+    );
+    assert_event_shape(
+        &env,
+        EscrowCancelledEvent {
+            depositor: a.clone(),
+        },
+        "EscrowCancelled",
+        &["depositor"],
+    );
+    assert_event_shape(
+        &env,
+        RefundExpiredEvent {
+            depositor: a.clone(),
+            refund_amount: 0,
+            refunded_at: 0,
+        },
+        "RefundExpired",
+        &["depositor", "refund_amount", "refunded_at"],
+    );
+    assert_event_shape(
+        &env,
+        EscrowArchivedEvent {
+            final_status: EscrowStatus::Completed,
+            record_hash: hash,
+        },
+        "EscrowArchived",
+        &["final_status", "record_hash"],
+    );
+
+    assert_contract_event_shape(
+        &env,
+        ContractUpgradedEvent {
+            wasm_hash: BytesN::from_array(&env, &[0; 32]),
+        },
+        "ContractUpgraded",
+        &["wasm_hash"],
+    );
+    assert_contract_event_shape(
+        &env,
+        RoleUpdatedEvent {
+            role: symbol_short!("Admin"),
+            address: a.clone(),
+        },
+        "RoleUpdated",
+        &["role", "address"],
+    );
+    assert_contract_event_shape(
+        &env,
+        FeeUpdatedEvent {
+            fee: Symbol::new(&env, "ArbiterFee"),
+            old_bps: 0,
+            new_bps: 0,
+        },
+        "FeeUpdated",
+        &["fee", "old_bps", "new_bps"],
+    );
+    assert_contract_event_shape(
+        &env,
+        PausedStateChangedEvent {
+            paused: false,
+            treasury: a.clone(),
+        },
+        "PausedStateChanged",
+        &["paused", "treasury"],
+    );
+    assert_contract_event_shape(
+        &env,
+        PauseFlagsUpdatedEvent {
+            flags: 0,
+            treasury: a.clone(),
+        },
+        "PauseFlagsUpdated",
+        &["flags", "treasury"],
+    );
+    assert_contract_event_shape(
+        &env,
+        TokenPauseUpdatedEvent {
+            token: a.clone(),
+            flags: 0,
+            treasury: b.clone(),
+        },
+        "TokenPauseUpdated",
+        &["token", "flags", "treasury"],
+    );
+    assert_contract_event_shape(
+        &env,
+        EmergencyModeChangedEvent {
+            enabled: false,
+            treasury: a.clone(),
+        },
+        "EmergencyModeChanged",
+        &["enabled", "treasury"],
+    );
+    assert_contract_event_shape(
+        &env,
+        PanelConfiguredEvent {
+            arbiters: vec![&env],
+            quorum: 0,
+            threshold: 0,
+        },
+        "PanelConfigured",
+        &["arbiters", "quorum", "threshold"],
+    );
+    assert_contract_event_shape(
+        &env,
+        AppealConfigUpdatedEvent {
+            window: 0,
+            bond: 0,
+            arbiter: a.clone(),
+        },
+        "AppealConfigUpdated",
+        &["window", "bond", "arbiter"],
+    );
+    assert_contract_event_shape(
+        &env,
+        DisputeBondUpdatedEvent {
+            token_address: a.clone(),
+            old_amount: 0,
+            new_amount: 0,
+        },
+        "DisputeBondUpdated",
+        &["token_address", "old_amount", "new_amount"],
+    );
+    assert_contract_event_shape(
+        &env,
+        MilestoneLimitUpdatedEvent {
+            old_limit: 0,
+            new_limit: 0,
+        },
+        "MilestoneLimitUpdated",
+        &["old_limit", "new_limit"],
+    );
+    assert_contract_event_shape(
+        &env,
+        TemplateRegisteredEvent {
+            template_id: 0,
+            owner: a.clone(),
+        },
+        "TemplateRegistered",
+        &["template_id", "owner"],
+    );
+    assert_contract_event_shape(
+        &env,
+        EscrowRequestedEvent {
+            request_id: 0,
+            depositor: a.clone(),
+            recipient: b.clone(),
+            expires_at: 0,
+        },
+        "EscrowRequested",
+        &["request_id", "depositor", "recipient", "expires_at"],
+    );
+    assert_contract_event_shape(
+        &env,
+        EscrowRequestAcceptedEvent {
+            request_id: 0,
+            escrow_id: 0,
+        },
+        "EscrowRequestAccepted",
+        &["request_id", "escrow_id"],
+    );
+    assert_contract_event_shape(
+        &env,
+        EscrowRequestRejectedEvent {
+            request_id: 0,
+            depositor: a.clone(),
+        },
+        "EscrowRequestRejected",
+        &["request_id", "depositor"],
+    );
+    assert_contract_event_shape(
+        &env,
+        EscrowRequestCancelledEvent {
+            request_id: 0,
+            recipient: b.clone(),
+        },
+        "EscrowRequestCancelled",
+        &["request_id", "recipient"],
+    );
+}
+
+#[test]
+fn test_escrow_events_carry_version_and_sequence() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, VaultixEscrow);
+    let client = VaultixEscrowClient::new(&env, &contract_id);
+
+    let depositor = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let escrow_id = 504u64;
+
+    let (token_client, token_admin, token_address) = create_token_contract(&env, &admin);
+    token_admin.mint(&depositor, &10_000);
+
+    let milestones = vec![
+        &env,
+        Milestone {
+            amount: 10_000,
+            status: MilestoneStatus::Pending,
+            description: symbol_short!("Task"),
+            due_date: 0,
+            review_period: 0,
+            submitted_at: 0,
+            kind: MilestoneKind::Fixed,
+            claimed: 0,
+            condition: ReleaseCondition::None,
+            payees: vec![&env],
+            approval: ApprovalRule::Depositor,
+            metadata: MilestoneMetadata::empty(&env),
+        },
+    ];
+
+    client.create_escrow(
+        &escrow_id,
+        &depositor,
+        &recipient,
+        &token_address,
+        &milestones,
+        &1706400000u64,
+    );
+    token_client.approve(&depositor, &contract_id, &10_000, &200);
+    client.deposit_funds(&escrow_id);
+    client.confirm_delivery(&escrow_id, &0, &depositor);
+    client.complete_escrow(&escrow_id);
+
+    let names = [
+        "EscrowCreated",
+        "EscrowFunded",
+        "MilestoneReleased",
+        "EscrowCompleted",
+    ];
+    let mut sequence = 0u64;
+    for (contract, topics, data) in env.events().all().iter() {
+        if contract != contract_id {
+            continue;
+        }
+        let expected_topics: soroban_sdk::Vec<Val> = (
+            Symbol::new(&env, "Vaultix"),
+            Symbol::new(&env, names[sequence as usize]),
+            EVENT_VERSION,
+            escrow_id,
+        )
+            .into_val(&env);
+        assert_eq!(topics, expected_topics);
+
+        let envelope: soroban_sdk::Vec<Val> = data.into_val(&env);
+        assert_eq!(envelope.len(), 2);
+        sequence += 1;
+        let stamped: u64 = envelope.get(0).unwrap().into_val(&env);
+        assert_eq!(stamped, sequence);
+    }
+    assert_eq!(sequence, 4);
+    assert_eq!(client.get_escrow_checkpoint(&escrow_id).sequence, 4);
+
+    let (_, released): (u64, MilestoneReleasedEvent) = env
+        .events()
+        .all()
+        .iter()
+        .filter(|(contract, _, _)| *contract == contract_id)
+        .nth(2)
+        .unwrap()
+        .2
+        .into_val(&env);
+    assert_eq!(
+        released,
+        MilestoneReleasedEvent {
+            milestone_index: 0,
+            payout: 10_000,
+            fee: 0,
+        }
+    );
+}
@@ -23,6 +23,15 @@ fn test_admin_upgrade_and_state_preservation() {
         amount: 1000,
         status: MilestoneStatus::Pending,
         description: Symbol::new(&env, "Test"),
+        due_date: 0,
+        review_period: 0,
+        submitted_at: 0,
+        kind: MilestoneKind::Fixed,
+        claimed: 0,
+        condition: ReleaseCondition::None,
+        payees: vec![&env],
+        approval: ApprovalRule::Depositor,
+        metadata: MilestoneMetadata::empty(&env),
     });
     let deadline = 1706400000u64;
     client.create_escrow(&1u64, &depositor, &recipient, &token_address, &milestones, &deadline);